@@ -2,6 +2,8 @@
 # This script installs only missing or incompatible project dependencies to the project's node_modules.
 # Base node_modules at /home/user/node_modules is immutable and never copied.
 # Uses check-base-dependencies.js to avoid duplicating packages that exist in base.
+# With arguments, they are the install command of the project's package manager, which
+# installs the project's lockfile exactly instead (e.g. npm ci --omit=dev).
 
 set -e
 
@@ -18,6 +20,15 @@ fi
 
 echo "Base node_modules found at /home/user/node_modules (immutable)"
 
+# Install exactly what the lockfile says; packages from base are not reused then
+if [ $# -gt 0 ]; then
+    echo "Installing project dependencies with: $*"
+    "$@"
+    mkdir -p node_modules
+    echo "Node.js project prepared successfully"
+    exit 0
+fi
+
 # Install project dependencies if package.json exists
 if [ -f "package.json" ]; then
     echo "Analyzing dependencies against base node_modules..."
@@ -12,7 +12,13 @@ curl -LsSf https://astral.sh/uv/install.sh | sh
 export UV_HTTP_TIMEOUT=3600
 . /home/user/.local/bin/env
 . /home/user/the_venv/bin/activate
-uv pip install -c /home/user/constraints.txt -r pyproject.toml
+# Older setuptools projects have a setup.py or setup.cfg instead of a pyproject.toml
+for manifest in pyproject.toml setup.py setup.cfg; do
+    if [[ -f "$manifest" ]]; then
+        uv pip install -c /home/user/constraints.txt -r "$manifest"
+        break
+    fi
+done
 
 # First find all files which have changed, if any has changed, we abort:
 cd /home/user
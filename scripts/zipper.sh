@@ -10,8 +10,13 @@
 # Node.js projects:
 #   - node_modules/ at /project/{project-name}/node_modules (project-specific dependencies)
 #   - project directory at /project/{project-name} (includes node_modules automatically)
+#
+# Usage: zipper.sh <project-name> [output-path]
+# The archive is written to output-path (default: /tmp/project.tar.gz).
 set -e
 
+OUTPUT_PATH="${2:-/tmp/project.tar.gz}"
+
 cd /project
 
 # Build tar command with only existing files/directories
@@ -45,8 +50,8 @@ fi
 # Create archive only if we have something to archive
 if [ ${#TAR_ARGS[@]} -gt 0 ]; then
     echo "Archiving: ${TAR_ARGS[*]}"
-    tar czvf /tmp/project.tar.gz "${TAR_ARGS[@]}"
-    echo "✓ Archive created successfully at /tmp/project.tar.gz"
+    tar czvf "$OUTPUT_PATH" "${TAR_ARGS[@]}"
+    echo "✓ Archive created successfully at $OUTPUT_PATH"
 else
     echo "ERROR: No files to archive found"
     exit 1
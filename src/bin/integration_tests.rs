@@ -1,8 +1,12 @@
 use clap::Parser;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 #[derive(Parser)]
 #[command(name = "integration_tests")]
@@ -11,16 +15,177 @@ struct Args {
     /// Skip the test which runs the base image with mounting the zip file
     #[arg(long)]
     no_zip_test: bool,
+
+    /// Print every command before running it, including the servicemaker runs
+    #[arg(long)]
+    print_commands: bool,
+
+    /// Number of test projects tested at the same time
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: u16,
+
+    /// Only test the projects whose directory name contains this text
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Also write the results as a JUnit XML report to this file
+    #[arg(long, value_name = "PATH")]
+    junit: Option<PathBuf>,
+
+    /// Keep the temporary directory and the image of a failed test project for
+    /// debugging, instead of removing them
+    #[arg(long)]
+    keep_on_failure: bool,
+
+    /// Fail a test project that takes longer than this many seconds, killing the
+    /// command that is running
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    test_timeout: Option<u64>,
+}
+
+/// A test project did not finish within --test-timeout
+#[derive(Debug)]
+struct TestTimeout(String);
+
+impl std::fmt::Display for TestTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Timed out (--test-timeout) while running: {}", self.0)
+    }
+}
+
+impl std::error::Error for TestTimeout {}
+
+/// Outcome of testing one project
+struct TestResult {
+    project_name: String,
+    duration: Duration,
+    error: Option<String>,
+}
+
+static PRINT_COMMANDS: AtomicBool = AtomicBool::new(false);
+
+trait CommandExt {
+    fn logged(&mut self) -> &mut Self;
+}
+
+impl CommandExt for Command {
+    fn logged(&mut self) -> &mut Self {
+        if PRINT_COMMANDS.load(Ordering::Relaxed) {
+            println!("+ {:?}", self);
+        }
+        self
+    }
+}
+
+/// Run `command` to completion, killing it when `deadline` has passed. Only the
+/// output redirected to `Stdio::piped()` is collected, the rest is passed through
+fn run_until(
+    command: &mut Command,
+    deadline: Option<Instant>,
+) -> Result<Output, Box<dyn std::error::Error>> {
+    let mut child = command.spawn()?;
+    let Some(deadline) = deadline else {
+        return Ok(child.wait_with_output()?);
+    };
+
+    // Drain the pipes while waiting, a full pipe would block the child
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut buffer = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buffer);
+            }
+            buffer
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Output {
+                status,
+                stdout: stdout.join().unwrap_or_default(),
+                stderr: stderr.join().unwrap_or_default(),
+            });
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(TestTimeout(format!("{:?}", command)).into());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Run the `docker run` of a test container named `container_name` and collect
+/// its output. The container is removed if the command fails or times out
+fn run_test_container(
+    command: &mut Command,
+    container_name: &str,
+    deadline: Option<Instant>,
+) -> Result<Output, Box<dyn std::error::Error>> {
+    run_until(
+        command.stdout(Stdio::piped()).stderr(Stdio::piped()),
+        deadline,
+    )
+    .map_err(|e| {
+        remove_container(container_name);
+        if e.is::<TestTimeout>() {
+            e
+        } else {
+            format!("Failed to run docker command: {}", e).into()
+        }
+    })
+}
+
+/// Force-remove a container, e.g. one whose `docker run` was killed on timeout
+fn remove_container(container_name: &str) {
+    let _ = Command::new("docker")
+        .args(["rm", "-f", container_name])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .logged()
+        .status();
 }
 
 #[derive(serde::Deserialize)]
 struct TestConfig {
     base_image: String,
     entrypoint: String,
+    /// Text the service prints when run from the image and from the tar.gz
+    #[serde(default = "default_expected_output")]
+    expected_output: String,
+    /// Regular expression the output must match instead of containing `expected_output`
+    #[serde(default)]
+    expected_regex: Option<String>,
+}
+
+fn default_expected_output() -> String {
+    "Hello World!".to_string()
+}
+
+impl TestConfig {
+    /// Check `output` against `expected_regex` if set, else look for `expected_output`
+    fn output_matches(&self, output: &str) -> Result<bool, regex::Error> {
+        match self.expected_regex {
+            Some(ref pattern) => Ok(regex::Regex::new(pattern)?.is_match(output)),
+            None => Ok(output.contains(&self.expected_output)),
+        }
+    }
+
+    /// What is expected of the output, for error messages
+    fn expectation(&self) -> String {
+        match self.expected_regex {
+            Some(ref pattern) => format!("match /{}/", pattern),
+            None => format!("contain '{}'", self.expected_output),
+        }
+    }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    PRINT_COMMANDS.store(args.print_commands, Ordering::Relaxed);
 
     println!("=== Integration Tests ===\n");
 
@@ -43,6 +208,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Err("No test directories found in testprojects/".into());
     }
 
+    let test_dirs = match args.filter {
+        Some(ref filter) => filter_test_directories(test_dirs, filter)?,
+        None => test_dirs,
+    };
+
     println!("Found {} test project(s):", test_dirs.len());
     for dir in &test_dirs {
         println!("  - {}", dir.display());
@@ -68,25 +238,69 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     cleanup_leftover_temp_directories(&project_root)?;
     println!();
 
-    let mut failed_projects: Vec<(String, String)> = Vec::new();
-
-    // Process each test directory
-    for test_dir in &test_dirs {
-        let project_name = test_dir.file_name().unwrap().to_string_lossy().to_string();
-        println!("=== Testing: {} ===", project_name);
-
-        match test_project(&project_root, test_dir, args.no_zip_test) {
-            Ok(_) => {
-                println!("✓ Test passed for {}\n", project_name);
-            }
-            Err(e) => {
-                let error_msg = e.to_string();
-                eprintln!("✗ Test failed for {}: {}\n", project_name, error_msg);
-                failed_projects.push((project_name, error_msg));
-            }
+    let results: Mutex<Vec<(usize, TestResult)>> = Mutex::new(Vec::new());
+
+    // Process the test directories in order, on up to --jobs threads
+    let next_test = AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        for _ in 0..usize::from(args.jobs).min(test_dirs.len()) {
+            scope.spawn(|| {
+                loop {
+                    let index = next_test.fetch_add(1, Ordering::Relaxed);
+                    let Some(test_dir) = test_dirs.get(index) else {
+                        break;
+                    };
+                    let project_name = test_dir.file_name().unwrap().to_string_lossy().to_string();
+                    println!("=== Testing: {} ===", project_name);
+
+                    let start = Instant::now();
+                    let deadline = args
+                        .test_timeout
+                        .map(|secs| start + Duration::from_secs(secs));
+                    let error = match test_project(
+                        &project_root,
+                        test_dir,
+                        args.no_zip_test,
+                        args.keep_on_failure,
+                        deadline,
+                    ) {
+                        Ok(_) => {
+                            println!("✓ Test passed for {}\n", project_name);
+                            None
+                        }
+                        Err(e) => {
+                            let error_msg = e.to_string();
+                            eprintln!("✗ Test failed for {}: {}\n", project_name, error_msg);
+                            Some(error_msg)
+                        }
+                    };
+                    results.lock().unwrap().push((
+                        index,
+                        TestResult {
+                            project_name,
+                            duration: start.elapsed(),
+                            error,
+                        },
+                    ));
+                }
+            });
         }
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    let results: Vec<TestResult> = results.into_iter().map(|(_, result)| result).collect();
+
+    if let Some(ref junit_path) = args.junit {
+        fs::write(junit_path, junit_report(&results))
+            .map_err(|e| format!("Failed to write {}: {}", junit_path.display(), e))?;
+        println!("JUnit report written to {}", junit_path.display());
     }
 
+    let failed_projects: Vec<(&str, &str)> = results
+        .iter()
+        .filter_map(|result| Some((result.project_name.as_str(), result.error.as_deref()?)))
+        .collect();
+
     // Print summary
     println!("=== Test Summary ===");
     if failed_projects.is_empty() {
@@ -101,6 +315,43 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
+/// Render the results as a JUnit XML test suite with one test case per project
+fn junit_report(results: &[TestResult]) -> String {
+    let failures = results.iter().filter(|r| r.error.is_some()).count();
+    let total: Duration = results.iter().map(|r| r.duration).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuite name=\"servicemaker-integration\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        results.len(),
+        failures,
+        total.as_secs_f64()
+    ));
+    for result in results {
+        xml.push_str(&format!(
+            "  <testcase name=\"{}\" classname=\"integration_tests\" time=\"{:.3}\"",
+            xml_escape(&result.project_name),
+            result.duration.as_secs_f64()
+        ));
+        match result.error {
+            Some(ref error) => xml.push_str(&format!(
+                ">\n    <failure message=\"{}\">{}</failure>\n  </testcase>\n",
+                xml_escape(error.lines().next().unwrap_or("")),
+                xml_escape(error)
+            )),
+            None => xml.push_str("/>\n"),
+        }
+    }
+    xml.push_str("</testsuite>\n");
+    xml
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 fn find_test_directories(
     testprojects_dir: &Path,
 ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
@@ -121,6 +372,33 @@ fn find_test_directories(
     Ok(dirs)
 }
 
+/// Keep the test directories whose name contains `filter`
+fn filter_test_directories(
+    test_dirs: Vec<PathBuf>,
+    filter: &str,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let project_names: Vec<String> = test_dirs
+        .iter()
+        .map(|dir| dir.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    let selected: Vec<PathBuf> = test_dirs
+        .into_iter()
+        .zip(&project_names)
+        .filter(|(_, name)| name.contains(filter))
+        .map(|(dir, _)| dir)
+        .collect();
+
+    if selected.is_empty() {
+        return Err(format!(
+            "No test project matches --filter '{}'. Available projects: {}",
+            filter,
+            project_names.join(", ")
+        )
+        .into());
+    }
+    Ok(selected)
+}
+
 fn collect_base_images(test_dirs: &[PathBuf]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
     let mut base_images = HashSet::new();
 
@@ -149,6 +427,7 @@ fn pull_base_images(base_images: &[String]) -> Result<(), Box<dyn std::error::Er
         println!("Pulling base image: {}", image);
         let output = Command::new("docker")
             .args(["pull", image])
+            .logged()
             .output()
             .map_err(|e| format!("Failed to run docker pull command for {}: {}", image, e))?;
 
@@ -176,7 +455,7 @@ fn cleanup_leftover_temp_directories(
             && let Some(dir_name) = path.file_name().and_then(|n| n.to_str())
         {
             // Match directories starting with "servicemaker-" and containing at least one more "-"
-            // This matches patterns like "servicemaker-<name>-<pid>"
+            // This matches patterns like "servicemaker-<name>-<pid>-<suffix>"
             if dir_name.starts_with("servicemaker-") && dir_name.matches('-').count() >= 2 {
                 println!("Removing leftover temporary directory: {}", path.display());
                 fs::remove_dir_all(&path).map_err(|e| {
@@ -207,6 +486,8 @@ fn test_project(
     project_root: &Path,
     test_dir: &Path,
     skip_zip_test: bool,
+    keep_on_failure: bool,
+    deadline: Option<Instant>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let project_name = test_dir.file_name().unwrap().to_string_lossy().to_string();
     let config_path = test_dir.join("config.json");
@@ -237,58 +518,118 @@ fn test_project(
     println!("\n--- Pre-test cleanup ---");
     remove_docker_image_if_exists(&image_name)?;
 
-    // Run servicemaker
-    println!("\nRunning servicemaker...");
-    let servicemaker_status = Command::new(&servicemaker_binary)
-        .args([
-            "--name",
-            &project_name,
-            "--project-home",
-            project_home.to_str().unwrap(),
-            "--base-image",
-            &config.base_image,
-            "--image-name",
-            &image_name,
-            "--entrypoint",
-            &config.entrypoint,
-            "--port",
-            "8080",
-            "--make-tar-gz",
-            // Note: push is false by default, so we don't need to specify it
-        ])
-        .current_dir(project_root)
-        .status()?;
-
-    if !servicemaker_status.success() {
-        return Err(format!(
-            "servicemaker failed with exit code: {:?}",
-            servicemaker_status.code()
-        )
-        .into());
-    }
+    // Remember the project contents; servicemaker must only write to its temporary directory
+    let project_contents = read_dir_contents(project_home)?;
 
-    println!("✓ servicemaker completed successfully");
-
-    // Find the temporary directory created by servicemaker
     let temp_dir_pattern = format!("servicemaker-{}-", project_name);
-    let temp_dir = find_temp_directory(project_root, &temp_dir_pattern)?;
-    println!("Found temporary directory: {}", temp_dir.display());
+    let result = (|| -> Result<PathBuf, Box<dyn std::error::Error>> {
+        // Run servicemaker
+        println!("\nRunning servicemaker...");
+        let mut servicemaker_command = Command::new(&servicemaker_binary);
+        servicemaker_command
+            .args([
+                "--name",
+                &project_name,
+                "--project-home",
+                project_home.to_str().unwrap(),
+                "--base-image",
+                &config.base_image,
+                "--image-name",
+                &image_name,
+                "--entrypoint",
+                &config.entrypoint,
+                "--port",
+                "8080",
+                "--make-tar-gz",
+                // Do not stop at the confirmation when run from a terminal
+                "--yes",
+                // Note: push is false by default, so we don't need to specify it
+            ])
+            .args(
+                PRINT_COMMANDS
+                    .load(Ordering::Relaxed)
+                    .then_some("--print-commands"),
+            )
+            .current_dir(project_root)
+            .logged();
+        let servicemaker_status = run_until(&mut servicemaker_command, deadline)?.status;
 
-    // Test 1: Run Docker image directly
-    println!("\n--- Test 1: Running Docker image ---");
-    test_docker_image(&image_name)?;
+        if !servicemaker_status.success() {
+            return Err(format!(
+                "servicemaker failed with exit code: {:?}",
+                servicemaker_status.code()
+            )
+            .into());
+        }
 
-    // Test 2: Run using tar.gz approach (skip if --no-zip-test is set)
-    if skip_zip_test {
-        println!("\n--- Test 2: Skipped (--no-zip-test flag set) ---");
-    } else {
-        println!("\n--- Test 2: Running with tar.gz file ---");
-        let tar_file = temp_dir.join("project.tar.gz");
-        if !tar_file.exists() {
-            return Err(format!("project.tar.gz not found at: {}", tar_file.display()).into());
+        println!("✓ servicemaker completed successfully");
+
+        if read_dir_contents(project_home)? != project_contents {
+            return Err(format!(
+                "servicemaker modified the project home: {}",
+                project_home.display()
+            )
+            .into());
         }
-        test_tar_gz_approach(&temp_dir, &tar_file, &config.base_image)?;
-    }
+        println!("✓ Project home is unchanged");
+
+        // Find the temporary directory created by servicemaker
+        let temp_dir = find_temp_directory(project_root, &temp_dir_pattern)?;
+        println!("Found temporary directory: {}", temp_dir.display());
+        check_permissions_preserved(project_home, &temp_dir.join(&project_name))?;
+        println!("✓ File permissions are preserved in the build context");
+
+        // Test 1: Run Docker image directly
+        println!("\n--- Test 1: Running Docker image ---");
+        test_docker_image(&image_name, &config, deadline)?;
+
+        // Test 2: Run using tar.gz approach (skip if --no-zip-test is set)
+        if skip_zip_test {
+            println!("\n--- Test 2: Skipped (--no-zip-test flag set) ---");
+        } else {
+            println!("\n--- Test 2: Running with tar.gz file ---");
+            let tar_file = temp_dir.join("project.tar.gz");
+            if !tar_file.exists() {
+                return Err(format!("project.tar.gz not found at: {}", tar_file.display()).into());
+            }
+            if !temp_dir.join("project.tar.gz.sha256").exists() {
+                return Err("project.tar.gz.sha256 not found next to project.tar.gz".into());
+            }
+            test_tar_gz_approach(&temp_dir, &tar_file, &config, deadline)?;
+        }
+
+        // Test 3: Service name precedence (--name, configuration file, project manifest)
+        println!("\n--- Test 3: Service name resolution ---");
+        test_service_name_resolution(&servicemaker_binary, project_home, &config, deadline)?;
+
+        Ok(temp_dir)
+    })();
+    let temp_dir = match result {
+        Ok(temp_dir) => temp_dir,
+        Err(e) => {
+            let temp_dir = find_temp_directory(project_root, &temp_dir_pattern).ok();
+            if keep_on_failure {
+                println!("\n--- Keeping artifacts of the failed test ---");
+                match temp_dir {
+                    Some(ref temp_dir) => println!("Temporary directory: {}", temp_dir.display()),
+                    None => println!("Temporary directory: (not created)"),
+                }
+                println!("Docker image: {}", image_name);
+            } else {
+                // Leave a clean environment behind, wherever the test failed
+                println!("\n--- Cleanup after failure ---");
+                if let Some(ref temp_dir) = temp_dir
+                    && let Err(cleanup_error) = cleanup_temp_directory(temp_dir)
+                {
+                    eprintln!("{}", cleanup_error);
+                }
+                if let Err(cleanup_error) = remove_docker_image_if_exists(&image_name) {
+                    eprintln!("{}", cleanup_error);
+                }
+            }
+            return Err(e);
+        }
+    };
 
     // Cleanup: Remove temporary directory and Docker image
     println!("\n--- Cleanup ---");
@@ -298,6 +639,188 @@ fn test_project(
     Ok(())
 }
 
+/// Check that the Helm chart is named after --name, else the configuration file, else the
+/// name in the project manifest (pyproject.toml / package.json), made a valid Kubernetes name
+fn test_service_name_resolution(
+    servicemaker_binary: &Path,
+    project_home: &Path,
+    config: &TestConfig,
+    deadline: Option<Instant>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // One directory per project, as projects may be tested concurrently (--jobs)
+    let project_name = project_home.file_name().unwrap().to_string_lossy();
+    let work_dir = std::env::temp_dir().join(format!(
+        "servicemaker-names-{}-{}",
+        project_name,
+        std::process::id()
+    ));
+    fs::create_dir_all(&work_dir)?;
+    let config_file = work_dir.join("servicemaker.toml");
+    fs::write(&config_file, "name = \"name-from-config\"\n")?;
+
+    let manifest_name = read_manifest_name(project_home)?;
+    let config_arg = config_file.to_str().unwrap();
+    let cases: [(&str, &[&str], &str); 5] = [
+        (
+            "--name",
+            &["--name", "name-from-flag", "--config", config_arg],
+            "name-from-flag",
+        ),
+        (
+            "configuration file",
+            &["--config", config_arg],
+            "name-from-config",
+        ),
+        ("project manifest", &[], &manifest_name),
+        (
+            "scoped npm name",
+            &["--name", "@org/scoped-name"],
+            "scoped-name",
+        ),
+        (
+            "unscoped name",
+            &["--name", "Name_From.Flag"],
+            "name-from-flag",
+        ),
+    ];
+
+    let result = cases.iter().try_for_each(|(source, extra_args, expected)| {
+        println!("Service name from {}: expecting {}", source, expected);
+        let mut servicemaker_command = Command::new(servicemaker_binary);
+        servicemaker_command
+            .args([
+                "--project-home",
+                project_home.to_str().unwrap(),
+                "--base-image",
+                &config.base_image,
+                "--entrypoint",
+                &config.entrypoint,
+                "--image-name",
+                "servicemaker-name-test",
+                "--port",
+                "8080",
+                "--chart-only",
+                "--cleanup",
+                "always",
+            ])
+            .args(*extra_args)
+            .args(
+                PRINT_COMMANDS
+                    .load(Ordering::Relaxed)
+                    .then_some("--print-commands"),
+            )
+            .current_dir(&work_dir)
+            .stdin(Stdio::null())
+            .logged();
+        let status = run_until(&mut servicemaker_command, deadline)?.status;
+        if !status.success() {
+            return Err(format!("servicemaker failed for service name from {}", source).into());
+        }
+
+        // The packaged chart is moved to the working directory as <service-name>-<version>.tgz
+        let prefix = format!("{}-", expected);
+        let mut found = false;
+        for entry in fs::read_dir(&work_dir)? {
+            let path = entry?.path();
+            let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+            if file_name.ends_with(".tgz") {
+                found |= file_name.starts_with(&prefix);
+                fs::remove_file(&path)?;
+            }
+        }
+        if found {
+            println!("✓ Chart named after the service name from {}", source);
+            Ok(())
+        } else {
+            Err(format!(
+                "Helm chart is not named {}* for service name from {}",
+                prefix, source
+            )
+            .into())
+        }
+    });
+
+    fs::remove_dir_all(&work_dir)?;
+    result
+}
+
+/// Read the project name from pyproject.toml (`[project]` or Poetry's `[tool.poetry]`) or
+/// package.json
+fn read_manifest_name(project_home: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let pyproject = project_home.join("pyproject.toml");
+    let name = if pyproject.exists() {
+        let value: toml::Value = toml::from_str(&fs::read_to_string(pyproject)?)?;
+        value
+            .get("project")
+            .or_else(|| value.get("tool").and_then(|t| t.get("poetry")))
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .map(|n| n.to_string())
+    } else {
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(project_home.join("package.json"))?)?;
+        value
+            .get("name")
+            .and_then(|n| n.as_str())
+            .map(|n| n.to_string())
+    };
+    name.ok_or_else(|| format!("No project name found in {}", project_home.display()).into())
+}
+
+/// Check that the copy of the project in the build context has the permission bits of the
+/// project's files, and that the executable entrypoint (e.g. hello.py, mode 0755) stays so
+fn check_permissions_preserved(
+    project_home: &Path,
+    copy: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut executables = 0;
+    for path in read_dir_contents(project_home)?.keys() {
+        let copied = copy.join(path.strip_prefix(project_home)?);
+        // Skipped directories such as node_modules are not copied
+        let Ok(copied_metadata) = fs::metadata(&copied) else {
+            continue;
+        };
+        let mode = fs::metadata(path)?.permissions().mode() & 0o7777;
+        let copied_mode = copied_metadata.permissions().mode() & 0o7777;
+        if mode != copied_mode {
+            return Err(format!(
+                "{} has mode {:o} in the build context instead of {:o}",
+                copied.display(),
+                copied_mode,
+                mode
+            )
+            .into());
+        }
+        if mode & 0o111 != 0 {
+            executables += 1;
+        }
+    }
+    println!("  {} executable file(s) stayed executable", executables);
+    Ok(())
+}
+
+/// Read all files below `dir`, keyed by their path
+fn read_dir_contents(dir: &Path) -> Result<BTreeMap<PathBuf, Vec<u8>>, Box<dyn std::error::Error>> {
+    let mut contents = BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                let content = fs::read(&path)?;
+                contents.insert(path, content);
+            }
+        }
+    }
+
+    Ok(contents)
+}
+
 fn find_temp_directory(
     project_root: &Path,
     pattern: &str,
@@ -306,9 +829,16 @@ fn find_temp_directory(
         let entry = entry?;
         let path = entry.path();
 
+        // The rest of the name must be "<pid>-<random hex suffix>", so that another
+        // project whose name starts with this one's ("foo" and "foo-bar") does not match
         if path.is_dir()
             && let Some(dir_name) = path.file_name().and_then(|n| n.to_str())
-            && dir_name.starts_with(pattern)
+            && let Some(rest) = dir_name.strip_prefix(pattern)
+            && let Some((pid, suffix)) = rest.split_once('-')
+            && !pid.is_empty()
+            && pid.chars().all(|c| c.is_ascii_digit())
+            && !suffix.is_empty()
+            && suffix.chars().all(|c| c.is_ascii_hexdigit())
         {
             return Ok(path);
         }
@@ -321,13 +851,22 @@ fn find_temp_directory(
     .into())
 }
 
-fn test_docker_image(image_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn test_docker_image(
+    image_name: &str,
+    config: &TestConfig,
+    deadline: Option<Instant>,
+) -> Result<(), Box<dyn std::error::Error>> {
     println!("Running: docker run --rm {}", image_name);
 
-    let output = Command::new("docker")
-        .args(["run", "--rm", image_name])
-        .output()
-        .map_err(|e| format!("Failed to run docker command: {}", e))?;
+    // Named, so that the container can be removed if `docker run` is killed
+    let container_name = format!("{}-test", image_name.replace('/', "-"));
+    let output = run_test_container(
+        Command::new("docker")
+            .args(["run", "--rm", "--name", &container_name, image_name])
+            .logged(),
+        &container_name,
+        deadline,
+    )?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -342,9 +881,10 @@ fn test_docker_image(image_name: &str) -> Result<(), Box<dyn std::error::Error>>
     let stdout = String::from_utf8_lossy(&output.stdout);
     println!("Output:\n{}", stdout);
 
-    if !stdout.contains("Hello World!") {
+    if !config.output_matches(&stdout)? {
         return Err(format!(
-            "Expected output to contain 'Hello World!', but got:\n{}",
+            "Expected output to {}, but got:\n{}",
+            config.expectation(),
             stdout
         )
         .into());
@@ -357,8 +897,10 @@ fn test_docker_image(image_name: &str) -> Result<(), Box<dyn std::error::Error>>
 fn test_tar_gz_approach(
     temp_dir: &Path,
     tar_file: &Path,
-    base_image: &str,
+    config: &TestConfig,
+    deadline: Option<Instant>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let base_image = config.base_image.as_str();
     // Get absolute path for the tar file
     let tar_file_abs = tar_file.canonicalize()?;
 
@@ -368,17 +910,27 @@ fn test_tar_gz_approach(
     );
     println!("(from directory: {})", temp_dir.display());
 
-    let output = Command::new("docker")
-        .args([
-            "run",
-            "--rm",
-            "-v",
-            &format!("{}:/project/project.tar.gz", tar_file_abs.display()),
-            base_image,
-        ])
-        .current_dir(temp_dir)
-        .output()
-        .map_err(|e| format!("Failed to run docker command: {}", e))?;
+    // Named, so that the container can be removed if `docker run` is killed
+    let container_name = format!(
+        "{}-tar-gz-test",
+        temp_dir.file_name().unwrap().to_string_lossy()
+    );
+    let output = run_test_container(
+        Command::new("docker")
+            .args([
+                "run",
+                "--rm",
+                "--name",
+                &container_name,
+                "-v",
+                &format!("{}:/project/project.tar.gz", tar_file_abs.display()),
+                base_image,
+            ])
+            .current_dir(temp_dir)
+            .logged(),
+        &container_name,
+        deadline,
+    )?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -393,12 +945,13 @@ fn test_tar_gz_approach(
     let stdout = String::from_utf8_lossy(&output.stdout);
     println!("Output:\n{}", stdout);
 
-    // Check if the last line contains "Hello World!"
+    // Check the last line against the expected output
     let lines: Vec<&str> = stdout.lines().collect();
     if let Some(last_line) = lines.last() {
-        if !last_line.contains("Hello World!") {
+        if !config.output_matches(last_line)? {
             return Err(format!(
-                "Expected last line to contain 'Hello World!', but got:\n{}",
+                "Expected last line to {}, but got:\n{}",
+                config.expectation(),
                 last_line
             )
             .into());
@@ -428,6 +981,7 @@ fn remove_docker_image_if_exists(image_name: &str) -> Result<(), Box<dyn std::er
     println!("Removing Docker image if it exists: {}", image_name);
     let output = Command::new("docker")
         .args(["rmi", image_name])
+        .logged()
         .output()
         .map_err(|e| format!("Failed to run docker rmi command: {}", e))?;
 
@@ -451,6 +1005,7 @@ fn cleanup_docker_image(image_name: &str) -> Result<(), Box<dyn std::error::Erro
     println!("Removing Docker image: {}", image_name);
     let output = Command::new("docker")
         .args(["rmi", image_name])
+        .logged()
         .output()
         .map_err(|e| format!("Failed to run docker rmi command: {}", e))?;
 
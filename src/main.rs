@@ -1,14 +1,519 @@
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
+use log::{debug, info, warn};
+use minijinja::{Environment, UndefinedBehavior, context};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::io::{self, Write};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, IsTerminal, Write};
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use toml::Value;
 
 // Default base images
 const DEFAULT_PYTHON_BASE_IMAGE: &str = "arangodb/py12base:latest";
 const DEFAULT_NODEJS_BASE_IMAGE: &str = "arangodb/node22base:latest";
+const DEFAULT_RUST_BASE_IMAGE: &str = "debian:bookworm-slim";
+const DEFAULT_DENO_BASE_IMAGE: &str = "denoland/deno:debian";
+const DEFAULT_BUN_BASE_IMAGE: &str = "oven/bun:1";
+
+// Image of the build stage of Rust projects
+const RUST_BUILDER_IMAGE: &str = "rust:1-bookworm";
+
+// Base images published from baseimages/ which can be selected by runtime version
+const KNOWN_BASE_IMAGES: &[&str] = &["arangodb/py12base:latest", "arangodb/node22base:latest"];
+
+// How often `docker cp` of project.tar.gz is attempted before giving up
+const TAR_GZ_COPY_ATTEMPTS: u32 = 3;
+
+// Set by --json: stdout is reserved for the JSON report, so everything else goes to stderr
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Print a progress or status line: to stdout, or to stderr with --json
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if JSON_OUTPUT.load(Ordering::Relaxed) {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+/// Like `status!` without the trailing newline, e.g. for prompts
+macro_rules! status_inline {
+    ($($arg:tt)*) => {
+        if JSON_OUTPUT.load(Ordering::Relaxed) {
+            eprint!($($arg)*)
+        } else {
+            print!($($arg)*)
+        }
+    };
+}
+
+/// Where external tools (docker, helm) write their output: stdout, stderr with
+/// --json, or nowhere when --log-level hides informational output
+fn child_stdout() -> Stdio {
+    if !log::log_enabled!(log::Level::Info) {
+        Stdio::null()
+    } else if JSON_OUTPUT.load(Ordering::Relaxed) {
+        Stdio::from(io::stderr())
+    } else {
+        Stdio::inherit()
+    }
+}
+
+/// Log external commands at debug level before they are run
+trait CommandExt {
+    fn logged(&mut self) -> &mut Self;
+}
+
+impl CommandExt for Command {
+    fn logged(&mut self) -> &mut Self {
+        let level = if PRINT_COMMANDS.load(Ordering::Relaxed) {
+            log::Level::Info
+        } else {
+            log::Level::Debug
+        };
+        log::log!(target: COMMAND_LOG_TARGET, level, "Running {}", command_line(self));
+        self
+    }
+}
+
+static PRINT_COMMANDS: AtomicBool = AtomicBool::new(false);
+
+// Log target of the executed commands, so that --print-commands can show them
+// even when the log level is below info
+const COMMAND_LOG_TARGET: &str = "servicemaker::commands";
+
+// Arguments whose value is a credential and must never be logged
+const SECRET_ARGS: &[&str] = &["--password", "-p", "--registry-password"];
+
+/// Render a command as it could be typed into a shell, including the working
+/// directory if one is set. Values of `SECRET_ARGS` are replaced by "[redacted]"
+fn command_line(command: &Command) -> String {
+    let mut parts = vec![shell_quote(&command.get_program().to_string_lossy())];
+    let mut redact_next = false;
+    for arg in command.get_args() {
+        let arg = arg.to_string_lossy();
+        if redact_next {
+            parts.push("[redacted]".to_string());
+            redact_next = false;
+            continue;
+        }
+        match arg.split_once('=') {
+            Some((name, _)) if SECRET_ARGS.contains(&name) => {
+                parts.push(format!("{}=[redacted]", name));
+            }
+            _ => {
+                redact_next = SECRET_ARGS.contains(&arg.as_ref());
+                parts.push(shell_quote(&arg));
+            }
+        }
+    }
+    let line = parts.join(" ");
+    match command.get_current_dir() {
+        Some(dir) => format!("{} (in {})", line, dir.display()),
+        None => line,
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@,+%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+// Python version assumed when it cannot be derived from the project or the base image
+const FALLBACK_PYTHON_VERSION: &str = "3.12";
+
+// Version used for the Helm chart of custom projects, which have no manifest to read it from
+const DEFAULT_CUSTOM_VERSION: &str = "1.0.0";
+
+// Version of a setup.py project whose setup.cfg does not state one
+const DEFAULT_SETUPTOOLS_VERSION: &str = "0.1.0";
+
+// Directories which are neither copied into the image nor searched for entrypoints
+const SKIPPED_DIRS: &[&str] = &[".venv", "node_modules"];
+
+/// Test and cache files of a project type which are not copied into the image unless
+/// --no-default-excludes is given (same syntax as --exclude)
+fn default_excludes(project_type: &str) -> &'static [&'static str] {
+    match project_type {
+        "python" => &["tests", ".pytest_cache", ".mypy_cache", ".tox"],
+        "nodejs" => &["coverage", ".next/cache"],
+        "rust" => &["target"],
+        _ => &[],
+    }
+}
+
+// Default file name written by the configuration wizard
+const DEFAULT_CONFIG_FILE: &str = "servicemaker.toml";
+
+// Project types a detector script or the configuration file may name
+const PROJECT_TYPES: &[&str] = &["python", "nodejs", "rust", "deno", "custom"];
+
+// Unified Dockerfile template for the built-in project types
+const DOCKERFILE_TEMPLATE: &str = include_str!("../Dockerfile.template");
+
+// Multi-stage Dockerfile template for Rust projects
+const RUST_DOCKERFILE_TEMPLATE: &str = include_str!("../Dockerfile.rust.template");
+
+// Dockerfile template for Deno projects
+const DENO_DOCKERFILE_TEMPLATE: &str = include_str!("../Dockerfile.deno.template");
+
+// Dockerfile template for Node.js projects running on Bun
+const BUN_DOCKERFILE_TEMPLATE: &str = include_str!("../Dockerfile.bun.template");
+
+/// Category of a failure; determines the exit code and the `kind` field of
+/// `--error-format json`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorKind {
+    /// Unexpected failure, e.g. an I/O error
+    Internal,
+    /// Invalid or inconsistent command line / configuration values
+    Config,
+    /// Project type or project metadata could not be determined
+    Detection,
+    /// A required external tool (docker, helm, ...) is missing
+    Tooling,
+    DockerBuild,
+    DockerPush,
+    TarGz,
+    HelmLint,
+    HelmPackage,
+    HelmTemplate,
+    ChartPush,
+    /// `docker login` with --registry-user failed
+    RegistryLogin,
+    /// A docker command did not finish within --timeout
+    Timeout,
+    /// `helm upgrade --install` of --deploy failed
+    Deploy,
+}
+
+impl ErrorKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::Internal => "internal",
+            ErrorKind::Config => "config",
+            ErrorKind::Detection => "detection",
+            ErrorKind::Tooling => "tooling",
+            ErrorKind::DockerBuild => "docker_build",
+            ErrorKind::DockerPush => "docker_push",
+            ErrorKind::TarGz => "tar_gz",
+            ErrorKind::HelmLint => "helm_lint",
+            ErrorKind::HelmPackage => "helm_package",
+            ErrorKind::HelmTemplate => "helm_template",
+            ErrorKind::ChartPush => "chart_push",
+            ErrorKind::RegistryLogin => "registry_login",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Deploy => "deploy",
+        }
+    }
+
+    fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Internal => 1,
+            ErrorKind::Config => 2,
+            ErrorKind::Detection => 3,
+            ErrorKind::Tooling => 4,
+            ErrorKind::DockerBuild => 5,
+            ErrorKind::DockerPush => 6,
+            ErrorKind::TarGz => 7,
+            ErrorKind::HelmLint => 8,
+            ErrorKind::HelmPackage => 9,
+            ErrorKind::HelmTemplate => 10,
+            ErrorKind::ChartPush => 11,
+            ErrorKind::RegistryLogin => 12,
+            ErrorKind::Timeout => 13,
+            ErrorKind::Deploy => 14,
+        }
+    }
+}
+
+/// A secret command line value (e.g. a registry password) which is never printed,
+/// not even in `Debug` output
+#[derive(Clone)]
+struct Secret(String);
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl std::str::FromStr for Secret {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Secret(s.to_string()))
+    }
+}
+
+/// Credentials for `docker login` before pushing
+struct RegistryLogin {
+    user: String,
+    password: Secret,
+}
+
+/// An error with a known category and optional context (image, path, ...)
+#[derive(Debug, Clone)]
+struct ServiceMakerError {
+    kind: ErrorKind,
+    message: String,
+    context: Option<String>,
+}
+
+impl ServiceMakerError {
+    fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        ServiceMakerError {
+            kind,
+            message: message.into(),
+            context: None,
+        }
+    }
+
+    fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// The error as reported by --error-format json
+    fn to_json(&self) -> serde_json::Value {
+        let mut report = serde_json::json!({
+            "kind": self.kind.as_str(),
+            "message": self.message,
+        });
+        if let Some(ref context) = self.context {
+            report["context"] = serde_json::Value::String(context.clone());
+        }
+        report
+    }
+
+    /// Categorize an arbitrary error; errors without a category are internal
+    fn from_error(error: &(dyn std::error::Error + 'static)) -> Self {
+        match error.downcast_ref::<ServiceMakerError>() {
+            Some(error) => error.clone(),
+            None => ServiceMakerError::new(ErrorKind::Internal, error.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for ServiceMakerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ServiceMakerError {}
+
+/// Values substituted into the Dockerfile templates
+struct DockerfileParams<'a> {
+    base_image: &'a str,
+    project_dir: &'a str,
+    entrypoint: &'a str,
+    port: u16,
+    env_vars: &'a [(String, String)],
+    labels: &'a [(String, String)],
+    health_path: Option<&'a str>,
+    build_info: bool,
+}
+
+/// Inputs of the --make-tar-gz archive
+struct TarGzParams<'a> {
+    /// Where the archive is put on the host
+    tar_file_path: &'a Path,
+    temp_dir: &'a Path,
+    image_name: &'a str,
+    project_dir: &'a str,
+    entrypoint: &'a str,
+    /// Path of the archive inside the zipper container
+    tar_output_path: &'a str,
+    run_as_current_user: bool,
+    timeout: Option<Duration>,
+    /// Build the archive on the host instead of in a container
+    local: bool,
+}
+
+/// Values substituted into the embedded Helm chart templates
+struct ChartParams<'a> {
+    service_name: &'a str,
+    chart_version: &'a str,
+    chart_type: &'a str,
+    /// Project metadata, whose version is the chart's appVersion
+    metadata: &'a ProjectMetadata,
+    port: u16,
+    image_name: &'a str,
+    health_path: Option<&'a str>,
+    replicas: u32,
+    service_type: &'a str,
+    node_port: Option<u16>,
+    ingress_host: Option<&'a str>,
+    ingress_class: Option<&'a str>,
+    cpu_request: Option<&'a str>,
+    cpu_limit: Option<&'a str>,
+    memory_request: Option<&'a str>,
+    memory_limit: Option<&'a str>,
+    env: &'a [(String, String)],
+    config_map: &'a [(String, String)],
+    pull_secrets: &'a [String],
+    annotations: &'a [(String, String)],
+    k8s_labels: &'a [(String, String)],
+    environments: &'a [String],
+    /// Chart files as (path relative to the chart, content)
+    chart_files: &'a [(String, String)],
+    template_vars: &'a [(String, String)],
+    variants: &'a [ChartVariant],
+}
+
+/// An additional chart packaged from the same image, named `<service-name>-<name>`,
+/// whose values.yaml is the generated one merged with an overlay
+struct ChartVariant {
+    name: String,
+    values: serde_norway::Value,
+}
+
+/// Phases of a run that have completed, for the --summary-on-failure report.
+/// Atomic, since the chart is generated on another thread than the image build.
+#[derive(Default)]
+struct BuildOutcome {
+    image_built: AtomicBool,
+    image_pushed: AtomicBool,
+    tar_gz_created: AtomicBool,
+    chart_packaged: AtomicBool,
+    chart_pushed: AtomicBool,
+    deployed: AtomicBool,
+}
+
+impl BuildOutcome {
+    fn mark(flag: &AtomicBool) {
+        flag.store(true, Ordering::Relaxed);
+    }
+
+    /// The status of each phase (key, label, status) after a run that succeeded
+    /// or failed with `failure`
+    fn phase_statuses(
+        &self,
+        args: &Args,
+        failure: Option<ErrorKind>,
+    ) -> Vec<(&'static str, &'static str, &'static str)> {
+        // A timeout aborts either the image build or the tar.gz creation after it
+        let failure = failure.map(|failure| match failure {
+            ErrorKind::Timeout if !self.image_built.load(Ordering::Relaxed) => {
+                ErrorKind::DockerBuild
+            }
+            ErrorKind::Timeout => ErrorKind::TarGz,
+            failure => failure,
+        });
+        let phases = [
+            (
+                "image_build",
+                "Image built",
+                !args.chart_only,
+                &self.image_built,
+                &[ErrorKind::DockerBuild][..],
+            ),
+            (
+                "image_push",
+                "Image pushed",
+                args.push || args.platform.is_some(),
+                &self.image_pushed,
+                &[ErrorKind::DockerPush, ErrorKind::RegistryLogin][..],
+            ),
+            (
+                "tar_gz",
+                "tar.gz created",
+                args.make_tar_gz,
+                &self.tar_gz_created,
+                &[ErrorKind::TarGz][..],
+            ),
+            (
+                "chart_package",
+                "Helm chart packaged",
+                !args.no_helm,
+                &self.chart_packaged,
+                &[
+                    ErrorKind::HelmLint,
+                    ErrorKind::HelmTemplate,
+                    ErrorKind::HelmPackage,
+                ][..],
+            ),
+            (
+                "chart_push",
+                "Helm chart pushed",
+                args.chart_registry.is_some(),
+                &self.chart_pushed,
+                &[ErrorKind::ChartPush][..],
+            ),
+            (
+                "deploy",
+                "Helm release deployed",
+                args.deploy,
+                &self.deployed,
+                &[ErrorKind::Deploy][..],
+            ),
+        ];
+
+        phases
+            .into_iter()
+            .map(|(key, label, requested, done, failure_kinds)| {
+                let status = if !requested {
+                    "skipped"
+                } else if done.load(Ordering::Relaxed) {
+                    "yes"
+                } else if failure.is_some_and(|failure| failure_kinds.contains(&failure)) {
+                    "failed"
+                } else {
+                    "not reached"
+                };
+                (key, label, status)
+            })
+            .collect()
+    }
+
+    /// Print which phases completed before the run failed with `failure`
+    fn print_summary(&self, args: &Args, failure: ErrorKind) {
+        eprintln!("\nSummary of the failed run:");
+        for (_, label, status) in self.phase_statuses(args, Some(failure)) {
+            eprintln!("  {}: {}", label, status);
+        }
+    }
+}
+
+/// Result of a run, printed as a single JSON object on stdout with --json
+#[derive(Serialize, Default)]
+struct RunReport {
+    success: bool,
+    name: Option<String>,
+    project_type: Option<String>,
+    image_name: Option<String>,
+    tags: Vec<String>,
+    port: Option<u16>,
+    charts: Vec<PathBuf>,
+    manifests: Option<PathBuf>,
+    tar_gz: Option<PathBuf>,
+    /// Status of each phase: yes, failed, not reached or skipped
+    steps: BTreeMap<&'static str, &'static str>,
+    error: Option<serde_json::Value>,
+}
+
+/// Files produced by a run, which are kept when the temporary directory is removed
+struct BuildArtifacts {
+    charts: Vec<PathBuf>,
+    manifests: Option<PathBuf>,
+    tar_gz: Option<PathBuf>,
+}
 
 // Embedded chart files
 struct ChartFile {
@@ -16,6 +521,12 @@ struct ChartFile {
     content: &'static str,
 }
 
+// JSON Schema of the values of the built-in chart, added with --values-schema
+const VALUES_SCHEMA: ChartFile = ChartFile {
+    path: "values.schema.json",
+    content: include_str!("../charts/values.schema.json"),
+};
+
 const CHART_FILES: &[ChartFile] = &[
     ChartFile {
         path: "Chart.yaml",
@@ -33,6 +544,14 @@ const CHART_FILES: &[ChartFile] = &[
         path: "templates/deployment.yaml",
         content: include_str!("../charts/templates/deployment.yaml"),
     },
+    ChartFile {
+        path: "templates/configmap.yaml",
+        content: include_str!("../charts/templates/configmap.yaml"),
+    },
+    ChartFile {
+        path: "templates/ingress.yaml",
+        content: include_str!("../charts/templates/ingress.yaml"),
+    },
     ChartFile {
         path: "templates/route.yaml",
         content: include_str!("../charts/templates/route.yaml"),
@@ -83,7 +602,11 @@ const SCRIPT_FILES: &[ScriptFile] = &[
 /// A tool to wrap Python and Node.js projects as Docker services
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Name of the project
     #[arg(long)]
     name: Option<String>,
@@ -96,8 +619,33 @@ struct Args {
     #[arg(long)]
     base_image: Option<String>,
 
-    /// Exposed port number
+    /// Python version (major.minor, e.g. 3.12) of a Python project, overriding
+    /// the detection from the project files and the base image name
+    #[arg(long)]
+    python_version: Option<String>,
+
+    /// JavaScript runtime of a Node.js project: node, or bun (the default when the project
+    /// has a bun.lockb or bun.lock)
+    #[arg(long, value_parser = ["node", "bun"])]
+    runtime: Option<String>,
+
+    /// Package manager of a Node.js project: npm, yarn or pnpm (default: detected from
+    /// pnpm-lock.yaml or yarn.lock, else npm)
+    #[arg(long, value_parser = ["npm", "yarn", "pnpm"])]
+    package_manager: Option<String>,
+
+    /// package.json script compiling a Node.js project with a tsconfig.json in the image
+    /// (default: build)
+    #[arg(long)]
+    build_script: Option<String>,
+
+    /// Directory of the compiled output of a Node.js project with a tsconfig.json, where a
+    /// TypeScript entrypoint is run from (default: outDir of tsconfig.json, else dist)
     #[arg(long)]
+    out_dir: Option<String>,
+
+    /// Exposed port number (1-65535)
+    #[arg(long, value_parser = parse_port)]
     port: Option<u16>,
 
     /// Docker image name to push
@@ -117,558 +665,4968 @@ struct Args {
     /// Whether to create a tar.gz file with project files and virtual environment changes
     #[arg(long, default_value = "false")]
     make_tar_gz: bool,
-}
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut args = Args::parse();
+    /// When to remove the temporary directory: always, never, on-success or
+    /// on-success-and-lint-failure (which keeps it for unexpected failures)
+    #[arg(
+        long,
+        default_value = "never",
+        value_parser = ["always", "never", "on-success", "on-success-and-lint-failure"]
+    )]
+    cleanup: String,
 
-    // Track if base_image was explicitly set by user
-    let base_image_explicitly_set = args.base_image.is_some();
+    /// Also render the chart with helm template to <service-name>-manifests.yaml
+    #[arg(long)]
+    render: bool,
 
-    // Get project home first (prompt if needed)
-    if args.project_home.is_none() {
-        let path_str = prompt("Project home path")?;
-        args.project_home = Some(PathBuf::from(path_str));
-    }
-    let project_home = args.project_home.as_ref().unwrap();
+    /// Namespace passed to helm template with --render
+    #[arg(long, requires = "render")]
+    render_namespace: Option<String>,
 
-    // Validate project home exists
-    if !project_home.exists() {
-        return Err(format!("Project home does not exist: {}", project_home.display()).into());
-    }
+    /// Fail when helm lint reports warnings, not only on errors
+    #[arg(long)]
+    strict_lint: bool,
 
-    // Detect project type: "python" or "nodejs"
-    let project_type = detect_project_type(project_home)?;
-    println!("Detected project type: {}", project_type);
+    /// Build and push the image while the Helm chart is generated, instead of
+    /// generating and linting the chart first so that a failing chart stops the push
+    #[arg(long)]
+    push_anyway: bool,
 
-    // Handle project type-specific configuration
-    match project_type.as_str() {
-        "python" => {
-            // Python project: requires pyproject.toml
-            // Try to get name from pyproject.toml if not provided on command line
-            if args.name.is_none()
-                && let Ok(name) = read_name_from_pyproject(project_home)
-            {
-                args.name = Some(name);
-            }
-            
-            // Try to auto-detect entrypoint if exactly one .py file exists
-            if args.entrypoint.is_none()
-                && let Ok(Some(py_file)) = find_single_py_file(project_home)
-            {
-                args.entrypoint = Some(py_file);
-            }
+    /// OCI registry to push the packaged Helm chart to, e.g. oci://registry.example.com/charts
+    #[arg(long)]
+    chart_registry: Option<String>,
 
-            // Prompt for entrypoint if still not set
-            if args.entrypoint.is_none() {
-                args.entrypoint = Some(prompt("Python entrypoint script (e.g., main.py)")?);
-            }
+    /// Install or upgrade the Helm release --release-name in --namespace of the current
+    /// kube-context after the chart is packaged (asks for confirmation unless --yes)
+    #[arg(long, requires_all = ["release_name", "namespace"])]
+    deploy: bool,
 
-            // Set default base image for Python if not explicitly set
-            if !base_image_explicitly_set {
-                args.base_image = Some(DEFAULT_PYTHON_BASE_IMAGE.to_string());
-            }
-        }
-        "nodejs" => {
-            // Node.js project: requires package.json (no services.json or manifest.json)
-            // Try to get name from package.json if not provided
-            if args.name.is_none()
-                && let Ok(name) = read_name_from_package_json(project_home)
-            {
-                args.name = Some(name);
-            }
+    /// Name of the Helm release for --deploy
+    #[arg(long, requires = "deploy")]
+    release_name: Option<String>,
 
-            // Try to auto-detect entrypoint from package.json "main" field or "start" script
-            if args.entrypoint.is_none() {
-                if let Ok(Some(entrypoint)) = detect_nodejs_entrypoint(project_home) {
-                    args.entrypoint = Some(entrypoint);
-                } else {
-                    args.entrypoint = Some("index.js".to_string());
-                }
-            }
+    /// Kubernetes namespace to deploy to with --deploy
+    #[arg(long, requires = "deploy")]
+    namespace: Option<String>,
 
-            // Set default base image for Node.js if not explicitly set
-            if !base_image_explicitly_set {
-                args.base_image = Some(DEFAULT_NODEJS_BASE_IMAGE.to_string());
-            }
-        }
-        _ => {
-            return Err(format!("Unsupported project type: {}", project_type).into());
-        }
-    }
+    /// Answer yes to every confirmation (e.g. of --deploy) instead of asking, for CI
+    #[arg(short = 'y', long = "yes")]
+    assume_yes: bool,
 
-    // Prompt for name if still not set
-    if args.name.is_none() {
-        args.name = Some(prompt("Project name")?);
-    }
+    /// How project files are copied into the build context: auto uses copy-on-write
+    /// reflinks where the filesystem supports them, always requires them, never
+    /// always makes regular copies
+    #[arg(long, default_value = "auto", value_parser = ["auto", "always", "never"])]
+    reflink: String,
 
-    let initial_project_dir = project_home.file_name().unwrap().to_str().unwrap();
+    /// Copy what symlinks in the project point to into the build context, instead of
+    /// skipping them with a warning. Links to a directory that is already copied are skipped
+    #[arg(long)]
+    follow_symlinks: bool,
 
-    if args.port.is_none() {
-        let port_str = prompt("Exposed port number")?;
-        args.port = Some(port_str.parse().expect("Invalid port number"));
-    }
+    /// File or directory not to copy into the build context (can be repeated): a name
+    /// such as `docs` anywhere in the project, or a path such as `data/large.bin`
+    #[arg(long = "exclude", value_name = "PATH")]
+    excludes: Vec<String>,
 
-    if args.image_name.is_none() {
-        args.image_name = Some(prompt("Docker image name")?);
-    }
+    /// Copy the test and cache files that are excluded by default for the project type
+    /// (e.g. tests and .pytest_cache for Python)
+    #[arg(long)]
+    no_default_excludes: bool,
 
-    let name = args.name.as_ref().unwrap();
-    let project_home = args.project_home.as_ref().unwrap();
-    let image_name = args.image_name.as_ref().unwrap();
-    let port = args.port.unwrap();
-    let base_image = args.base_image.as_ref().unwrap();
+    /// Path inside the container where zipper.sh writes the archive for --make-tar-gz,
+    /// for base images where /tmp is not writable
+    #[arg(
+        long,
+        default_value = "/tmp/project.tar.gz",
+        value_name = "CONTAINER_PATH"
+    )]
+    tar_output_path: String,
 
-    println!("\n=== Configuration ===");
-    println!("Project name: {}", name);
-    println!("Project type: {}", project_type);
-    println!("Project home: {}", project_home.display());
-    println!("Project directory name: {}", initial_project_dir);
-    println!("Base image: {}", base_image);
-    println!("Port: {}", port);
-    println!("Image name: {}", image_name);
-    if let Some(ref entrypoint) = args.entrypoint {
-        println!("Entrypoint: {}", entrypoint);
-    }
-    println!("Push: {}", args.push);
-    println!("Make tar.gz: {}", args.make_tar_gz);
-    println!("=====================\n");
+    /// Where to put the archive of --make-tar-gz on the host (parent directories
+    /// are created). By default it is project.tar.gz in the temporary directory
+    #[arg(long, value_name = "PATH")]
+    tar_output: Option<PathBuf>,
 
-    // Create temporary directory
-    let temp_dir =
-        std::env::current_dir()?.join(format!("servicemaker-{}-{}", name, std::process::id()));
-    println!("Creating temporary directory: {}", temp_dir.display());
+    /// Create the --make-tar-gz archive on the host instead of in a container of
+    /// the image. Only for Python projects without dependencies in pyproject.toml;
+    /// others fall back to the container
+    #[arg(long)]
+    tar_local: bool,
 
-    if temp_dir.exists() {
-        fs::remove_dir_all(&temp_dir)?;
-    }
-    fs::create_dir_all(&temp_dir)?;
+    /// Do not write a <artifact>.sha256 checksum file next to the tar.gz and the
+    /// packaged charts
+    #[arg(long)]
+    no_checksum: bool,
 
-    // Copy scripts to temp directory with executable permissions
-    copy_scripts_to_temp(&temp_dir)?;
+    /// Run the --make-tar-gz container as the invoking user (uid:gid), so that the
+    /// archive is owned by that user. Falls back to the image's user if that fails
+    #[arg(long)]
+    run_as_current_user: bool,
 
-    // Copy project directory to temp directory
-    // Both Python and Node.js projects are copied directly without any special handling
-    let project_dest = temp_dir.join(initial_project_dir);
-    println!(
-        "Copying project from {} to {}",
-        project_home.display(),
-        project_dest.display()
-    );
-    copy_dir_recursive(project_home, &project_dest)?;
-    let project_dir = initial_project_dir.to_string();
+    /// User for `docker login` to the registry of --image-name before pushing
+    #[arg(long, requires = "push")]
+    registry_user: Option<String>,
 
-    // Read environment variables from .env.example if it exists
-    let env_vars = read_env_example(project_home)?;
-    if !env_vars.is_empty() {
-        println!("Found {} environment variable(s) in .env.example", env_vars.len());
-    }
+    /// Password for --registry-user. Prefer --registry-password-stdin, since
+    /// command line arguments are visible to other users of the machine
+    #[arg(
+        long,
+        requires = "registry_user",
+        conflicts_with = "registry_password_stdin"
+    )]
+    registry_password: Option<Secret>,
 
-    // Choose Dockerfile template and modify based on project type
-    let modified_dockerfile = match project_type.as_str() {
-        "python" => {
-            // Python project: use Python Dockerfile template
-            let python_version = extract_python_version(base_image);
-            let entrypoint = args.entrypoint.as_ref().unwrap();
-            let dockerfile_template = include_str!("../Dockerfile.template");
-            modify_dockerfile_python(
-                dockerfile_template,
-                base_image,
-                &project_dir,
-                entrypoint,
-                port,
-                &python_version,
-                &env_vars,
-            )
-        }
-        "nodejs" => {
-            // Node.js project: use Node.js Dockerfile template
-            let entrypoint = args.entrypoint.as_ref().unwrap();
-            let dockerfile_template = include_str!("../Dockerfile.nodejs.template");
-            modify_dockerfile_nodejs(
-                dockerfile_template,
-                base_image,
-                &project_dir,
-                entrypoint,
-                port,
-                &env_vars,
-            )
-        }
-        _ => return Err("Unsupported project type".into()),
-    };
+    /// Read the password for --registry-user from stdin
+    #[arg(long, requires = "registry_user")]
+    registry_password_stdin: bool,
 
-    // Write modified Dockerfile to temp directory
-    let dockerfile_path = temp_dir.join("Dockerfile");
-    fs::write(&dockerfile_path, modified_dockerfile)?;
-    println!("Created Dockerfile: {}", dockerfile_path.display());
+    /// How often a failed docker push is retried (with exponential backoff)
+    /// before giving up. Authentication errors are not retried
+    #[arg(long, default_value_t = 3)]
+    push_retries: u32,
 
-    // Build Docker image
-    println!("\nBuilding Docker image...");
-    let build_status = Command::new("docker")
-        .args(["build", "-f", "./Dockerfile", "-t", image_name, "."])
-        .current_dir(&temp_dir)
-        .status()?;
+    /// Abort the docker build and each step of the tar.gz creation after this
+    /// many seconds. A timed out zipper container is removed
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
 
-    if !build_status.success() {
-        return Err("Docker build failed".into());
-    }
+    /// Write the digest of the pushed image (repo@sha256:...) to this file
+    #[arg(long, value_name = "PATH", requires = "push")]
+    digest_file: Option<PathBuf>,
 
-    println!("\n✓ Docker image built successfully: {}", image_name);
+    /// Additional tag for the built image (can be repeated), either a tag such as
+    /// `latest` for the repository of --image-name or a full image reference
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
 
-    // Push Docker image if requested
-    if args.push {
-        println!("\nPushing Docker image...");
-        let push_status = Command::new("docker").args(["push", image_name]).status()?;
+    /// Write the full reference of the built image to this file, including the
+    /// registry digest if it can be resolved after the push
+    #[arg(long, value_name = "PATH")]
+    image_ref_file: Option<PathBuf>,
 
-        if !push_status.success() {
-            return Err("Docker push failed".into());
-        }
+    /// Also write the rendered Dockerfile to this file, e.g. to diff or commit it
+    #[arg(long, value_name = "PATH")]
+    dockerfile_out: Option<PathBuf>,
 
-        println!("✓ Docker image pushed successfully");
-    }
+    /// Directory to write the packaged Helm chart(s) to instead of the temporary
+    /// directory, e.g. ./dist/charts (created if missing)
+    #[arg(long, value_name = "DIR")]
+    chart_out: Option<PathBuf>,
 
-    // Create tar.gz file if requested
-    if args.make_tar_gz {
-        println!("\n=== Creating project.tar.gz ===");
+    /// Stop after building (and pushing) the image without generating a Helm chart
+    #[arg(long)]
+    no_helm: bool,
 
-        // Run container in detached mode to get container ID
-        let container_output = Command::new("docker")
-            .args([
-                "run",
-                "-d",
-                "--entrypoint",
-                "bash",
-                image_name,
-                "-c",
-                &format!("/scripts/zipper.sh {}", project_dir),
-            ])
-            .output()?;
+    /// Write build-info.json (git commit, build time, servicemaker version and
+    /// base image digest) into the image as /build-info.json
+    #[arg(long)]
+    include_git_metadata: bool,
 
-        if !container_output.status.success() {
-            return Err(format!(
-                "Failed to start Docker container: {}",
-                String::from_utf8_lossy(&container_output.stderr)
-            )
-            .into());
-        }
+    /// Verify that the project home is byte-identical after the run
+    #[arg(long)]
+    readonly_check: bool,
 
-        let container_id = String::from_utf8(container_output.stdout)?
-            .trim()
-            .to_string();
-        println!("Started container: {}", container_id);
+    /// Comma-separated environments (e.g. dev,staging,prod) to generate
+    /// values-<env>.yaml overlays for in the Helm chart
+    #[arg(long, value_delimiter = ',')]
+    environments: Vec<String>,
 
-        // Wait for container to finish
-        println!("Waiting for container to finish...");
-        let wait_status = Command::new("docker")
-            .args(["wait", &container_id])
-            .status()?;
+    /// Name of an image pull secret for the deployment's imagePullSecrets (can be
+    /// repeated)
+    #[arg(long = "pull-secret", value_name = "NAME")]
+    pull_secrets: Vec<String>,
 
-        if !wait_status.success() {
-            return Err("Failed to wait for container".into());
-        }
+    /// Annotation as KEY=VALUE for the metadata of the generated deployment and
+    /// service (can be repeated)
+    #[arg(long = "annotation", value_name = "KEY=VALUE")]
+    annotations: Vec<String>,
 
-        // Check exit code of the container
-        let exit_code_output = Command::new("docker")
-            .args(["inspect", "-f", "{{.State.ExitCode}}", &container_id])
-            .output()?;
+    /// Kubernetes label as KEY=VALUE for the metadata of the generated deployment
+    /// and service (can be repeated; --label sets image labels)
+    #[arg(long = "k8s-label", value_name = "KEY=VALUE")]
+    k8s_labels: Vec<String>,
 
-        if !exit_code_output.status.success() {
-            return Err("Failed to inspect container exit code".into());
-        }
+    /// Version of the Helm chart (default: the version of the project)
+    #[arg(long)]
+    chart_version: Option<String>,
 
-        let exit_code = String::from_utf8(exit_code_output.stdout)?
-            .trim()
-            .parse::<i32>()?;
+    /// Use this version instead of the one in the project manifest, which is not changed:
+    /// for the image label, the chart version and appVersion and an image tag
+    #[arg(long, conflicts_with = "bump", value_parser = parse_project_version)]
+    project_version: Option<String>,
 
-        if exit_code != 0 {
-            return Err(format!("Container exited with code: {}", exit_code).into());
-        }
+    /// Type of the generated Helm chart: application, or library for a chart whose
+    /// templates are only available as named templates to other charts
+    #[arg(long, default_value = "application", value_parser = ["application", "library"])]
+    chart_type: String,
 
-        // Copy file from container to temp directory
-        let tar_file_path = temp_dir.join("project.tar.gz");
-        println!("Copying project.tar.gz from container...");
-        let copy_status = Command::new("docker")
-            .args([
+    /// Directory with Helm chart templates (Chart.yaml, values.yaml, templates/...) to
+    /// use instead of the built-in chart. Placeholders such as {SERVICE_NAME} and
+    /// {IMAGE_NAME} are replaced as in the built-in chart
+    #[arg(long)]
+    chart_dir: Option<PathBuf>,
+
+    /// Add a values.schema.json to the Helm chart, so that helm lint, install and
+    /// upgrade check the types of the values
+    #[arg(long)]
+    values_schema: bool,
+
+    /// Additional chart packaged from the same image as NAME:VALUES_FILE (can be
+    /// repeated). The chart is named <service-name>-NAME and its values.yaml is the
+    /// generated one merged with VALUES_FILE
+    #[arg(long = "chart-variant", value_name = "NAME:VALUES_FILE")]
+    chart_variants: Vec<String>,
+
+    /// Only generate, lint and package the Helm chart without building the image
+    #[arg(long)]
+    chart_only: bool,
+
+    /// Extra image label as KEY=VALUE (can be repeated)
+    #[arg(long = "label", value_name = "KEY=VALUE")]
+    labels: Vec<String>,
+
+    /// Comma-separated image labels which must be present (via --label or
+    /// derived from the project) before building
+    #[arg(long, value_delimiter = ',')]
+    require_labels: Vec<String>,
+
+    /// Comma-separated registry hosts the base image may come from, e.g.
+    /// docker.io,registry.example.com (default: any registry)
+    #[arg(long, value_delimiter = ',')]
+    allowed_registries: Vec<String>,
+
+    /// HTTP path for health checks, e.g. /healthz
+    /// Adds a Dockerfile HEALTHCHECK and readiness/liveness probes to the Helm chart
+    #[arg(long)]
+    health_path: Option<String>,
+
+    /// Resolve the base image tag to its current digest and build FROM image@sha256:...
+    #[arg(long, default_value = "false")]
+    pin_base_image: bool,
+
+    /// Fail before building if an image named --image-name already exists locally
+    #[arg(long)]
+    no_overwrite: bool,
+
+    /// Overwrite an existing local image despite --no-overwrite
+    #[arg(long, requires = "no_overwrite")]
+    force: bool,
+
+    /// Increment this part of the project version in the project's manifest; the image
+    /// and chart get the new version, which is written to the project home only after
+    /// the run succeeded
+    #[arg(
+        long,
+        value_parser = ["major", "minor", "patch"],
+        conflicts_with = "readonly_check"
+    )]
+    bump: Option<String>,
+
+    /// Skip docker build if the local image was built from the same Dockerfile and
+    /// project files, recorded as a hash in an image label
+    #[arg(long)]
+    skip_if_exists: bool,
+
+    /// Number of replicas of the deployment in the Helm chart
+    #[arg(long, default_value_t = 1)]
+    replicas: u32,
+
+    /// Kubernetes service type of the generated Service
+    #[arg(long, default_value = "ClusterIP", value_parser = ["ClusterIP", "NodePort", "LoadBalancer"])]
+    service_type: String,
+
+    /// Node port for --service-type NodePort (30000-32767)
+    #[arg(long)]
+    node_port: Option<u16>,
+
+    /// Host name for a Kubernetes Ingress routing to the service
+    /// Without it, the Ingress is disabled in values.yaml
+    #[arg(long)]
+    ingress_host: Option<String>,
+
+    /// Ingress class (controller) for the Ingress, e.g. nginx
+    #[arg(long)]
+    ingress_class: Option<String>,
+
+    /// CPU request of the service container in the Helm chart, e.g. 100m
+    #[arg(long)]
+    cpu_request: Option<String>,
+
+    /// CPU limit of the service container in the Helm chart, e.g. 1
+    #[arg(long)]
+    cpu_limit: Option<String>,
+
+    /// Memory request of the service container in the Helm chart, e.g. 128Mi
+    #[arg(long)]
+    memory_request: Option<String>,
+
+    /// Memory limit of the service container in the Helm chart, e.g. 512Mi
+    #[arg(long)]
+    memory_limit: Option<String>,
+
+    /// Environment variable of the service container as KEY=VALUE (can be repeated)
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    env: Vec<String>,
+
+    /// Dotenv file (KEY=VALUE lines) whose entries are put into a ConfigMap
+    /// that is loaded into the service container's environment
+    #[arg(long)]
+    env_file: Option<PathBuf>,
+
+    /// Additional template placeholder as KEY=VALUE, replacing {KEY} in the
+    /// Dockerfile and Helm chart templates (can be repeated)
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<String>,
+
+    /// TOML or JSON file with additional template placeholders (key-value pairs)
+    /// Entries given with --set take precedence
+    #[arg(long)]
+    template_vars_file: Option<PathBuf>,
+
+    /// Target platforms for a multi-arch build, e.g. linux/amd64,linux/arm64
+    /// Builds with `docker buildx` and requires --push
+    #[arg(long)]
+    platform: Option<String>,
+
+    /// Path to a configuration file (as written by --interactive-config) providing defaults
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Script to run when the project type cannot be detected. It is called with the
+    /// project path as argument and must print the project type (python, nodejs or custom)
+    #[arg(long)]
+    detector_script: Option<PathBuf>,
+
+    /// Dockerfile template used instead of the built-in one (required for projects of
+    /// type "custom"). Supports the template variables of the built-in template and the
+    /// {BASE_IMAGE}, {PROJECT_DIR}, {PORT}, {ENTRYPOINT} and {PYTHON_VERSION} placeholders
+    #[arg(long)]
+    dockerfile: Option<PathBuf>,
+
+    /// Maximum level of the log messages on stderr: error, warn, info or debug
+    /// (debug also shows every external command). Below info, the output of
+    /// docker and helm is hidden as well
+    #[arg(long, default_value = "info", value_parser = ["error", "warn", "info", "debug"])]
+    log_level: String,
+
+    /// Print every external command (with its arguments and working directory)
+    /// regardless of --log-level, e.g. to reproduce a failing docker or helm call
+    #[arg(long)]
+    print_commands: bool,
+
+    /// Print a single JSON object describing the run (resolved values, artifacts
+    /// and the status of each step) on stdout; all other output goes to stderr
+    #[arg(long)]
+    json: bool,
+
+    /// On failure, also print which phases (image build, push, tar.gz, chart,
+    /// chart push) completed before the error
+    #[arg(long)]
+    summary_on_failure: bool,
+
+    /// Format of the error report on failure: human or json (printed to stderr)
+    #[arg(long, default_value = "human", value_parser = ["human", "json"])]
+    error_format: String,
+
+    /// Walk through all prompts and write a configuration file without building anything
+    #[arg(long, visible_alias = "wizard", default_value = "false")]
+    interactive_config: bool,
+
+    /// Options given on the command line or in the environment, which the configuration
+    /// file does not override even where they have a default (filled in by parse_args)
+    #[arg(skip)]
+    given: Vec<String>,
+}
+
+impl Args {
+    fn is_given(&self, id: &str) -> bool {
+        self.given.iter().any(|given| given == id)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Print a completion script for the given shell to stdout
+    #[command(hide = true)]
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+}
+
+/// Reusable build configuration, stored as TOML (see --config and --interactive-config)
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct ServiceConfig {
+    name: Option<String>,
+    project_home: Option<PathBuf>,
+    project_type: Option<String>,
+    base_image: Option<String>,
+    port: Option<u16>,
+    image_name: Option<String>,
+    entrypoint: Option<String>,
+    push: Option<bool>,
+    make_tar_gz: Option<bool>,
+    detector_script: Option<PathBuf>,
+    dockerfile: Option<PathBuf>,
+    allowed_registries: Option<Vec<String>>,
+    replicas: Option<u32>,
+    service_type: Option<String>,
+    ingress_host: Option<String>,
+    cpu_request: Option<String>,
+    cpu_limit: Option<String>,
+    memory_request: Option<String>,
+    memory_limit: Option<String>,
+    chart_type: Option<String>,
+    chart_version: Option<String>,
+}
+
+/// Prefix of the environment variables providing options not given on the command line
+const ENV_PREFIX: &str = "SERVICEMAKER_";
+
+/// Parse the command line. Every option falls back to an environment variable named
+/// after it, e.g. SERVICEMAKER_BASE_IMAGE for --base-image
+fn parse_args() -> Args {
+    let command = Args::command().mut_args(|mut arg| {
+        let Some(long) = arg.get_long() else {
+            return arg;
+        };
+        let env = format!("{}{}", ENV_PREFIX, long.to_uppercase().replace('-', "_"));
+        // Flags accept the usual spellings from CI systems, e.g. SERVICEMAKER_PUSH=1. A
+        // false one is ignored, since clap counts it as given for `requires` and conflicts
+        if matches!(arg.get_action(), ArgAction::SetTrue) {
+            let falsy = std::env::var(&env).is_ok_and(|value| {
+                matches!(
+                    value.to_lowercase().as_str(),
+                    "" | "0" | "n" | "no" | "f" | "false" | "off"
+                )
+            });
+            if falsy {
+                return arg;
+            }
+            arg = arg.value_parser(clap::builder::BoolishValueParser::new());
+        }
+        // The value of a secret must not show up in --help
+        let secret = arg.get_id() == "registry_password";
+        arg.env(env).hide_env_values(secret)
+    });
+    let matches = command.get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    args.given = matches
+        .ids()
+        .filter(|id| {
+            matches!(
+                matches.value_source(id.as_str()),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        })
+        .map(|id| id.to_string())
+        .collect();
+    args
+}
+
+fn main() {
+    let args = parse_args();
+    if let Some(Commands::Completions { shell }) = args.command {
+        let mut command = Args::command();
+        let name = command.get_name().to_string();
+        clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+        return;
+    }
+
+    let json_errors = args.error_format == "json";
+    let json_output = args.json;
+    JSON_OUTPUT.store(json_output, Ordering::Relaxed);
+    PRINT_COMMANDS.store(args.print_commands, Ordering::Relaxed);
+    init_logger(&args.log_level, args.print_commands);
+
+    let mut report = RunReport::default();
+    let result = run(args, &mut report);
+
+    if json_output {
+        report.success = result.is_ok();
+        report.error = result
+            .as_ref()
+            .err()
+            .map(|e| ServiceMakerError::from_error(e.as_ref()).to_json());
+        println!(
+            "{}",
+            serde_json::to_string(&report).expect("run report is serializable")
+        );
+    }
+
+    if let Err(e) = result {
+        let error = ServiceMakerError::from_error(e.as_ref());
+        if json_errors {
+            eprintln!("{}", error.to_json());
+        } else {
+            match error.context {
+                Some(ref context) => eprintln!("Error: {} ({})", error.message, context),
+                None => eprintln!("Error: {}", error.message),
+            }
+        }
+        std::process::exit(error.kind.exit_code());
+    }
+}
+
+/// Log to stderr up to `level`. Informational messages are printed as they are,
+/// others with their level, e.g. "Warning: ..."
+fn init_logger(level: &str, print_commands: bool) {
+    let level = level.parse().unwrap_or(log::LevelFilter::Info);
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(level);
+    if print_commands {
+        builder.filter_module(COMMAND_LOG_TARGET, level.max(log::LevelFilter::Info));
+    }
+    builder
+        .format(|buf, record| match record.level() {
+            log::Level::Info => writeln!(buf, "{}", record.args()),
+            log::Level::Warn => writeln!(buf, "Warning: {}", record.args()),
+            log::Level::Error => writeln!(buf, "Error: {}", record.args()),
+            level => writeln!(buf, "[{}] {}", level.as_str().to_lowercase(), record.args()),
+        })
+        .init();
+}
+
+fn run(mut args: Args, report: &mut RunReport) -> Result<(), Box<dyn std::error::Error>> {
+    if args.interactive_config {
+        return run_config_wizard(&args);
+    }
+
+    // Fill in values not given on the command line from the configuration file
+    let mut config_project_type = None;
+    if let Some(config_path) = args.config.clone() {
+        let config = load_config(&config_path)
+            .map_err(|e| ServiceMakerError::new(ErrorKind::Config, e.to_string()))?;
+        info!("Loaded configuration from {}", config_path.display());
+        config_project_type = config.project_type.clone();
+        apply_config(&mut args, config);
+    }
+
+    // Track if base_image was explicitly set by user
+    let base_image_explicitly_set = args.base_image.is_some();
+
+    // Get project home first (prompt if needed)
+    if args.project_home.is_none() {
+        let path_str = prompt("Project home path")?;
+        args.project_home = Some(PathBuf::from(path_str));
+    }
+    let project_home = args.project_home.as_ref().unwrap();
+
+    // Validate project home exists
+    if !project_home.exists() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!("Project home does not exist: {}", project_home.display()),
+        )
+        .into());
+    }
+
+    // Detect project type: "python", "nodejs", "rust", "deno" or (via detector script) "custom".
+    // A type in the configuration file (e.g. chosen in the wizard) takes precedence
+    let detected_type =
+        detect_project_type_with_script(project_home, args.detector_script.as_deref());
+    let project_type = match config_project_type {
+        Some(configured_type) => {
+            if !PROJECT_TYPES.contains(&configured_type.as_str()) {
+                return Err(ServiceMakerError::new(
+                    ErrorKind::Config,
+                    format!(
+                        "Unsupported project type '{}' in the configuration file. \
+                        Expected one of: {}",
+                        configured_type,
+                        PROJECT_TYPES.join(", ")
+                    ),
+                )
+                .into());
+            }
+            if let Ok(ref detected_type) = detected_type
+                && *detected_type != configured_type
+            {
+                warn!(
+                    "configuration file says project type '{}', but '{}' was detected; using '{}'",
+                    configured_type, detected_type, configured_type
+                );
+            }
+            info!("Project type from configuration file: {}", configured_type);
+            configured_type
+        }
+        None => {
+            let detected_type = detected_type?;
+            info!("Detected project type: {}", detected_type);
+            detected_type
+        }
+    };
+
+    for (flag, given) in [
+        ("--runtime", args.runtime.is_some()),
+        ("--package-manager", args.package_manager.is_some()),
+    ] {
+        if given && project_type != "nodejs" {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!("{} only applies to Node.js projects", flag),
+            )
+            .into());
+        }
+    }
+
+    // Handle project type-specific configuration
+    match project_type.as_str() {
+        "python" => {
+            // Python project: requires pyproject.toml
+            // Try to get name from pyproject.toml if not provided on command line
+            if args.name.is_none()
+                && let Ok(name) = read_name_from_pyproject(project_home)
+            {
+                args.name = Some(name);
+            }
+
+            // Try to auto-detect entrypoint if exactly one .py file exists
+            if args.entrypoint.is_none()
+                && let Ok(Some(py_file)) = find_single_py_file(project_home)
+            {
+                args.entrypoint = Some(py_file);
+            }
+
+            // Prompt for entrypoint if still not set
+            if args.entrypoint.is_none() {
+                args.entrypoint = Some(prompt("Python entrypoint script (e.g., main.py)")?);
+            }
+
+            // The entrypoint is relative to the project home and must exist there
+            let entrypoint = args.entrypoint.as_deref().unwrap();
+            if !project_home.join(entrypoint).is_file() {
+                let py_files = list_root_files(project_home, &["py"])?;
+                let found = if py_files.is_empty() {
+                    "no .py files found".to_string()
+                } else {
+                    format!("found: {}", py_files.join(", "))
+                };
+                return Err(ServiceMakerError::new(
+                    ErrorKind::Config,
+                    format!(
+                        "Entrypoint {} does not exist in {} ({})",
+                        entrypoint,
+                        project_home.display(),
+                        found
+                    ),
+                )
+                .into());
+            }
+
+            // Set base image for Python if not explicitly set: from .tool-versions or the default
+            if !base_image_explicitly_set {
+                args.base_image = Some(
+                    base_image_from_tool_versions(project_home, "python")
+                        .unwrap_or_else(|| DEFAULT_PYTHON_BASE_IMAGE.to_string()),
+                );
+            }
+        }
+        "nodejs" => {
+            // Node.js project: requires package.json (no services.json or manifest.json)
+            // A Bun lockfile selects the Bun runtime unless --runtime says otherwise
+            if args.runtime.is_none() && has_bun_lockfile(project_home) {
+                args.runtime = Some("bun".to_string());
+            }
+            let bun = args.runtime.as_deref() == Some("bun");
+            if bun {
+                info!("Runtime: bun");
+                if args.package_manager.is_some() {
+                    return Err(ServiceMakerError::new(
+                        ErrorKind::Config,
+                        "--package-manager does not apply to the Bun runtime, which uses bun install",
+                    )
+                    .into());
+                }
+            } else {
+                // The lockfile tells which package manager the project uses
+                if args.package_manager.is_none() {
+                    args.package_manager = Some(detect_package_manager(project_home).to_string());
+                }
+                info!(
+                    "Package manager: {}",
+                    args.package_manager.as_deref().unwrap()
+                );
+            }
+
+            // Try to get name from package.json if not provided
+            if args.name.is_none()
+                && let Ok(name) = read_name_from_package_json(project_home)
+            {
+                args.name = Some(name);
+            }
+
+            // A TypeScript project is compiled in the image (Bun runs TypeScript itself).
+            // From here on, --out-dir is set exactly for projects with a build step.
+            let typescript = !bun && project_home.join("tsconfig.json").exists();
+            if typescript {
+                if args.out_dir.is_none() {
+                    args.out_dir = Some(
+                        typescript_out_dir(project_home)?.unwrap_or_else(|| "dist".to_string()),
+                    );
+                }
+                let build_script = args.build_script.get_or_insert_with(|| "build".to_string());
+                if !package_json_has_script(project_home, build_script) {
+                    return Err(ServiceMakerError::new(
+                        ErrorKind::Config,
+                        format!(
+                            "tsconfig.json found, but package.json has no '{}' script to compile \
+                             the project (see --build-script)",
+                            build_script
+                        ),
+                    )
+                    .into());
+                }
+                info!(
+                    "TypeScript: compiled with the '{}' script into {}",
+                    build_script,
+                    args.out_dir.as_deref().unwrap()
+                );
+            } else if args.build_script.is_some() || args.out_dir.is_some() {
+                warn!("--build-script and --out-dir are ignored without a tsconfig.json");
+                args.build_script = None;
+                args.out_dir = None;
+            }
+
+            // Try to auto-detect entrypoint from package.json "main" field or "start" script
+            if args.entrypoint.is_none() {
+                if let Ok(Some(entrypoint)) = detect_nodejs_entrypoint(project_home) {
+                    args.entrypoint = Some(entrypoint);
+                } else if let Some(ref out_dir) = args.out_dir {
+                    args.entrypoint = Some(format!("{}/index.js", out_dir));
+                } else {
+                    args.entrypoint = Some("index.js".to_string());
+                }
+            }
+
+            // A TypeScript entrypoint runs as its compiled JavaScript in the output directory
+            if let Some(ref out_dir) = args.out_dir
+                && let Some(entrypoint) = args.entrypoint.as_deref()
+                && let Some(stem) = entrypoint.strip_suffix(".ts")
+            {
+                let compiled = compiled_entrypoint(project_home, out_dir, stem);
+                info!("Running {} as {}", entrypoint, compiled);
+                args.entrypoint = Some(compiled);
+            }
+
+            // The entrypoint may be built inside the image (e.g. dist/server.js),
+            // so a missing file is only a warning
+            let entrypoint = args.entrypoint.as_deref().unwrap();
+            let entrypoint_path = project_home.join(entrypoint);
+            if typescript {
+                // Compiled in the image
+            } else if !entrypoint_path.is_file() {
+                let js_files = list_root_files(project_home, &["js", "mjs", "cjs"])?;
+                let found = if js_files.is_empty() {
+                    "no .js or .mjs files found".to_string()
+                } else {
+                    format!("found: {}", js_files.join(", "))
+                };
+                warn!(
+                    "entrypoint {} does not exist in {} ({})",
+                    entrypoint,
+                    project_home.display(),
+                    found
+                );
+            } else if let Err(e) = fs::File::open(&entrypoint_path) {
+                warn!("entrypoint {} is not readable: {}", entrypoint, e);
+            }
+
+            // The tar.gz archive is built with the scripts of the Python/Node.js base images
+            if bun && args.make_tar_gz {
+                return Err(ServiceMakerError::new(
+                    ErrorKind::Config,
+                    "--make-tar-gz is not supported with the Bun runtime",
+                )
+                .into());
+            }
+
+            // Set base image for Node.js if not explicitly set: from .tool-versions,
+            // .nvmrc or package.json engines, or the default. Bun has its own image.
+            let declared_node = declared_node_version(project_home).filter(|_| !bun);
+            if !base_image_explicitly_set {
+                args.base_image = Some(if bun {
+                    DEFAULT_BUN_BASE_IMAGE.to_string()
+                } else {
+                    base_image_from_tool_versions(project_home, "nodejs")
+                        .or_else(|| {
+                            let (source, major) = declared_node.as_ref()?;
+                            base_image_for_node_major(*major, source)
+                        })
+                        .unwrap_or_else(|| DEFAULT_NODEJS_BASE_IMAGE.to_string())
+                });
+            }
+            if let (Some((source, major)), Some(base_image)) = (&declared_node, &args.base_image)
+                && let Some(image_major) = extract_node_major(base_image)
+                && image_major != *major
+            {
+                warn!(
+                    "{} requires Node.js {}, but base image {} provides Node.js {}",
+                    source, major, base_image, image_major
+                );
+            }
+        }
+        "rust" => {
+            // Rust project: requires Cargo.toml
+            // Try to get name from Cargo.toml if not provided
+            if args.name.is_none()
+                && let Ok(info) = read_service_info_from_cargo_toml(project_home)
+            {
+                args.name = Some(info.name);
+            }
+
+            // The entrypoint is the binary to run
+            if args.entrypoint.is_none() {
+                args.entrypoint = match detect_rust_binary(project_home) {
+                    Ok(Some(binary)) => Some(binary),
+                    _ => Some(prompt("Rust binary to run (e.g., myservice)")?),
+                };
+            }
+
+            // The tar.gz archive is built with the scripts of the Python/Node.js base images
+            if args.make_tar_gz {
+                return Err(ServiceMakerError::new(
+                    ErrorKind::Config,
+                    "--make-tar-gz is not supported for Rust projects",
+                )
+                .into());
+            }
+
+            // Set runtime base image for Rust if not explicitly set
+            if !base_image_explicitly_set {
+                args.base_image = Some(DEFAULT_RUST_BASE_IMAGE.to_string());
+            }
+        }
+        "deno" => {
+            // Deno project: requires deno.json or deno.jsonc
+            // Try to get name from deno.json if not provided
+            if args.name.is_none()
+                && let Ok(info) = read_service_info_from_deno_json(project_home)
+            {
+                args.name = Some(info.name);
+            }
+
+            // Without an entrypoint module the project is started with `deno task start`
+            if args.entrypoint.is_none() {
+                match detect_deno_entrypoint(project_home) {
+                    Ok(DenoStart::Task) => info!("Starting the project with: deno task start"),
+                    Ok(DenoStart::Module(module)) => args.entrypoint = Some(module),
+                    _ => {
+                        let module = prompt("Deno entrypoint module (e.g., main.ts)")?;
+                        if module.is_empty() {
+                            return Err(ServiceMakerError::new(
+                                ErrorKind::Config,
+                                "The Deno project has no start task, so an entrypoint module \
+                                 is required (--entrypoint)",
+                            )
+                            .into());
+                        }
+                        args.entrypoint = Some(module);
+                    }
+                }
+            }
+
+            // The tar.gz archive is built with the scripts of the Python/Node.js base images
+            if args.make_tar_gz {
+                return Err(ServiceMakerError::new(
+                    ErrorKind::Config,
+                    "--make-tar-gz is not supported for Deno projects",
+                )
+                .into());
+            }
+
+            // Set base image for Deno if not explicitly set
+            if !base_image_explicitly_set {
+                args.base_image = Some(DEFAULT_DENO_BASE_IMAGE.to_string());
+            }
+        }
+        "custom" => {
+            // Custom project: built from a user-supplied Dockerfile template
+            if args.dockerfile.is_none() {
+                return Err(ServiceMakerError::new(
+                    ErrorKind::Config,
+                    "Projects of type 'custom' require a Dockerfile template (--dockerfile)",
+                )
+                .into());
+            }
+
+            // There is no default base image for custom projects
+            if args.base_image.is_none() {
+                args.base_image = Some(prompt("Base Docker image")?);
+            }
+        }
+        _ => {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Detection,
+                format!("Unsupported project type: {}", project_type),
+            )
+            .into());
+        }
+    }
+
+    // Prompt for name if still not set
+    if args.name.is_none() {
+        args.name = Some(prompt("Project name")?);
+    }
+
+    let initial_project_dir = project_home.file_name().unwrap().to_str().unwrap();
+
+    // Ask again for an invalid port if someone is typing, else give up
+    while args.port.is_none() {
+        let port_str = prompt("Exposed port number")?;
+        match parse_port(&port_str) {
+            Ok(port) => args.port = Some(port),
+            Err(e) if io::stdin().is_terminal() => status!("{}", e),
+            Err(e) => return Err(ServiceMakerError::new(ErrorKind::Config, e).into()),
+        }
+    }
+
+    if args.image_name.is_none() {
+        args.image_name = Some(prompt("Docker image name")?);
+    }
+
+    let name = args.name.as_ref().unwrap();
+    let project_home = args.project_home.as_ref().unwrap();
+    let image_name = args.image_name.as_ref().unwrap();
+    let port = args.port.unwrap();
+    // A port from a --config file has not been checked yet
+    if port == 0 {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "Invalid port number 0: expected 1-65535",
+        )
+        .into());
+    }
+    if port < 1024 {
+        warn!(
+            "Port {} is privileged (below 1024): a container not running as root usually \
+             cannot bind it",
+            port
+        );
+    }
+    let base_image = args.base_image.as_ref().unwrap();
+
+    // The service name (temporary directory, Helm chart and Kubernetes resources) is the
+    // resolved project name: --name, the configuration file, the project manifest or the
+    // prompt, in that order, made a valid Kubernetes name. Labels keep the full name.
+    let service_name = sanitize_service_name(name).ok_or_else(|| {
+        ServiceMakerError::new(
+            ErrorKind::Config,
+            format!(
+                "Project name '{}' has no letters or digits to build a service name from",
+                name
+            ),
+        )
+    })?;
+    if service_name != *name {
+        warn!(
+            "Project name '{}' is not a valid Kubernetes name, using '{}' for the temporary \
+             directory and the Helm chart",
+            name, service_name
+        );
+    }
+
+    report.name = Some(name.clone());
+    report.project_type = Some(project_type.clone());
+    report.image_name = Some(image_name.clone());
+    report.port = Some(port);
+
+    info!("\n=== Configuration ===");
+    info!("Project name: {}", name);
+    info!("Project type: {}", project_type);
+    info!("Project home: {}", project_home.display());
+    info!("Project directory name: {}", initial_project_dir);
+    info!("Base image: {}", base_image);
+    info!("Port: {}", port);
+    info!("Image name: {}", image_name);
+    if let Some(ref entrypoint) = args.entrypoint {
+        info!("Entrypoint: {}", entrypoint);
+    }
+    if let Some(ref health_path) = args.health_path {
+        info!("Health path: {}", health_path);
+    }
+    info!("Replicas: {}", args.replicas);
+    info!("Service type: {}", args.service_type);
+    if let Some(ref ingress_host) = args.ingress_host {
+        info!("Ingress host: {}", ingress_host);
+    }
+    info!("Push: {}", args.push);
+    if let Some(ref platform) = args.platform {
+        info!("Platform: {}", platform);
+    }
+    info!("Make tar.gz: {}", args.make_tar_gz);
+    if args.chart_only {
+        info!("Chart only: true");
+    }
+    if args.no_helm {
+        info!("No Helm chart: true");
+    }
+    info!("=====================\n");
+
+    if let Some(ref health_path) = args.health_path
+        && !health_path.starts_with('/')
+    {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!("--health-path must start with '/': {}", health_path),
+        )
+        .into());
+    }
+
+    if args.replicas == 0 {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--replicas must be at least 1 (a deployment without replicas would not run the service)",
+        )
+        .into());
+    }
+
+    if let Some(node_port) = args.node_port {
+        if args.service_type != "NodePort" {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                "--node-port requires --service-type NodePort",
+            )
+            .into());
+        }
+        if !(30000..=32767).contains(&node_port) {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "--node-port must be in the range 30000-32767, got {}",
+                    node_port
+                ),
+            )
+            .into());
+        }
+    }
+
+    if args.ingress_class.is_some() && args.ingress_host.is_none() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--ingress-class requires --ingress-host",
+        )
+        .into());
+    }
+
+    for (flag, quantity) in [
+        ("--cpu-request", &args.cpu_request),
+        ("--cpu-limit", &args.cpu_limit),
+        ("--memory-request", &args.memory_request),
+        ("--memory-limit", &args.memory_limit),
+    ] {
+        if let Some(quantity) = quantity
+            && !is_valid_quantity(quantity)
+        {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "Invalid {} value '{}': expected a Kubernetes quantity like 500m, 1, 128Mi or 2Gi",
+                    flag, quantity
+                ),
+            )
+            .into());
+        }
+    }
+
+    let chart_files = match args.chart_dir {
+        Some(ref chart_dir) => read_chart_dir(chart_dir)?,
+        None => CHART_FILES
+            .iter()
+            .chain(args.values_schema.then_some(&VALUES_SCHEMA))
+            .map(|f| (f.path.to_string(), f.content.to_string()))
+            .collect(),
+    };
+
+    let chart_variants = args
+        .chart_variants
+        .iter()
+        .map(|entry| read_chart_variant(entry))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (i, variant) in chart_variants.iter().enumerate() {
+        if chart_variants[..i].iter().any(|v| v.name == variant.name) {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!("Duplicate --chart-variant name '{}'", variant.name),
+            )
+            .into());
+        }
+    }
+
+    for environment in &args.environments {
+        let valid = !environment.is_empty()
+            && environment
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "Invalid environment name '{}': use lowercase letters, digits and '-'",
+                    environment
+                ),
+            )
+            .into());
+        }
+    }
+
+    // Later --env entries override earlier ones with the same name
+    let mut deployment_env: Vec<(String, String)> = Vec::new();
+    for entry in &args.env {
+        let (key, value) = parse_key_value(entry, "--env")?;
+        deployment_env.retain(|(existing, _)| *existing != key);
+        deployment_env.push((key, value));
+    }
+
+    // Later entries override earlier ones with the same key
+    let mut annotations: Vec<(String, String)> = Vec::new();
+    for entry in &args.annotations {
+        let (key, value) = parse_key_value(entry, "--annotation")?;
+        validate_k8s_key(&key, "--annotation")?;
+        annotations.retain(|(existing, _)| *existing != key);
+        annotations.push((key, value));
+    }
+    let mut k8s_labels: Vec<(String, String)> = Vec::new();
+    for entry in &args.k8s_labels {
+        let (key, value) = parse_key_value(entry, "--k8s-label")?;
+        validate_k8s_key(&key, "--k8s-label")?;
+        if !is_k8s_name(&value) && !value.is_empty() {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "Invalid --k8s-label value '{}' for '{}': use at most 63 letters, digits, \
+                     '-', '_' and '.', starting and ending with a letter or digit",
+                    value, key
+                ),
+            )
+            .into());
+        }
+        k8s_labels.retain(|(existing, _)| *existing != key);
+        k8s_labels.push((key, value));
+    }
+
+    let config_map = match args.env_file {
+        Some(ref env_file) => read_env_file(env_file)
+            .map_err(|e| ServiceMakerError::new(ErrorKind::Config, e.to_string()))?,
+        None => Vec::new(),
+    };
+
+    // Multi-platform images cannot be loaded into the local daemon
+    if args.platform.is_some() && !args.push {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--platform requires --push, since multi-platform images \
+            cannot be stored in the local Docker daemon",
+        )
+        .into());
+    }
+
+    if let Some(ref chart_registry) = args.chart_registry {
+        if !chart_registry.starts_with("oci://") {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "Invalid --chart-registry '{}': expected an oci:// reference",
+                    chart_registry
+                ),
+            )
+            .into());
+        }
+        if args.no_helm {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                "--chart-registry and --no-helm cannot be used together",
+            )
+            .into());
+        }
+    }
+
+    // The schema describes the values of the built-in chart only
+    for (flag, set) in [
+        ("--chart-dir", args.chart_dir.is_some()),
+        ("--no-helm", args.no_helm),
+    ] {
+        if args.values_schema && set {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!("--values-schema and {} cannot be used together", flag),
+            )
+            .into());
+        }
+    }
+
+    if !args.chart_variants.is_empty() && args.no_helm {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--chart-variant and --no-helm cannot be used together",
+        )
+        .into());
+    }
+
+    if args.chart_out.is_some() && args.no_helm {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--chart-out and --no-helm cannot be used together",
+        )
+        .into());
+    }
+
+    if args.render && args.no_helm {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--render and --no-helm cannot be used together",
+        )
+        .into());
+    }
+
+    if args.deploy {
+        if args.no_helm {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                "--deploy and --no-helm cannot be used together",
+            )
+            .into());
+        }
+        if args.chart_type == "library" {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                "--deploy cannot be used with --chart-type library",
+            )
+            .into());
+        }
+        // Helm release names are DNS labels of at most 53 characters
+        let release_name = args.release_name.as_deref().unwrap();
+        if !is_dns_label(release_name) || release_name.len() > 53 {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "Invalid --release-name '{}': expected at most 53 lowercase letters, \
+                     digits and '-', starting and ending with a letter or digit",
+                    release_name
+                ),
+            )
+            .into());
+        }
+        let namespace = args.namespace.as_deref().unwrap();
+        if !is_dns_label(namespace) {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "Invalid --namespace '{}': expected a Kubernetes namespace name",
+                    namespace
+                ),
+            )
+            .into());
+        }
+    }
+
+    // helm template refuses to render library charts
+    if args.render && args.chart_type == "library" {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--render cannot be used with --chart-type library",
+        )
+        .into());
+    }
+
+    // The path is passed to zipper.sh through bash -c and must not need quoting
+    if !args.tar_output_path.starts_with('/')
+        || args.tar_output_path.ends_with('/')
+        || !args
+            .tar_output_path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-/".contains(c))
+    {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!(
+                "Invalid --tar-output-path '{}': expected an absolute file path \
+                of letters, digits, '.', '_', '-' and '/'",
+                args.tar_output_path
+            ),
+        )
+        .into());
+    }
+
+    if args.chart_only && args.no_helm {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--chart-only and --no-helm cannot be used together",
+        )
+        .into());
+    }
+
+    if let Some(ref python_version) = args.python_version
+        && python_major_minor(python_version).as_deref() != Some(python_version.trim())
+    {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!(
+                "Invalid --python-version '{}': expected major.minor, e.g. 3.12",
+                python_version
+            ),
+        )
+        .into());
+    }
+
+    // Only base images from approved registries may be used
+    if !args.allowed_registries.is_empty() {
+        let registry = image_registry(base_image);
+        if !args
+            .allowed_registries
+            .iter()
+            .any(|allowed| allowed.trim().eq_ignore_ascii_case(registry))
+        {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "Base image {} is from registry {}, which is not in the allowed registries: {}",
+                    base_image,
+                    registry,
+                    args.allowed_registries.join(", ")
+                ),
+            )
+            .into());
+        }
+    }
+
+    // Everything that needs the image is skipped in chart-only mode
+    if args.chart_only {
+        for (flag, set) in [
+            ("--push", args.push),
+            ("--make-tar-gz", args.make_tar_gz),
+            ("--platform", args.platform.is_some()),
+            ("--pin-base-image", args.pin_base_image),
+            ("--no-overwrite", args.no_overwrite),
+            ("--skip-if-exists", args.skip_if_exists),
+            ("--image-ref-file", args.image_ref_file.is_some()),
+            ("--tag", !args.tags.is_empty()),
+        ] {
+            if set {
+                return Err(ServiceMakerError::new(
+                    ErrorKind::Config,
+                    format!("{} cannot be used with --chart-only", flag),
+                )
+                .into());
+            }
+        }
+    }
+
+    // buildx pushes a multi-platform image without keeping it locally
+    if args.skip_if_exists && args.platform.is_some() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--skip-if-exists cannot be used with --platform",
+        )
+        .into());
+    }
+
+    let mut extra_image_refs = args
+        .tags
+        .iter()
+        .map(|tag| extra_image_ref(image_name, tag))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let registry_login = match args.registry_user {
+        Some(ref user) => {
+            let password = match args.registry_password {
+                Some(ref password) => password.clone(),
+                None if args.registry_password_stdin => read_password_from_stdin()?,
+                None => {
+                    return Err(ServiceMakerError::new(
+                        ErrorKind::Config,
+                        "--registry-user requires --registry-password or --registry-password-stdin",
+                    )
+                    .into());
+                }
+            };
+            Some(RegistryLogin {
+                user: user.clone(),
+                password,
+            })
+        }
+        None => None,
+    };
+
+    preflight_check(&args)?;
+
+    // A last look at the configuration printed above before the build starts. Runs
+    // without a terminal (e.g. in CI) are not asked, as if --yes was given
+    if !args.assume_yes
+        && io::stdin().is_terminal()
+        && !confirm("Proceed with this configuration?", false)?
+    {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "Aborted: the configuration was not confirmed, nothing was built",
+        )
+        .into());
+    }
+
+    // Confirm the deployment before anything is built, so that declining costs nothing
+    if args.deploy {
+        let question = format!(
+            "Deploy release {} to namespace {} of the current kube-context?",
+            args.release_name.as_deref().unwrap(),
+            args.namespace.as_deref().unwrap()
+        );
+        if !confirm(&question, args.assume_yes)? {
+            return Err(
+                ServiceMakerError::new(ErrorKind::Config, "Deployment not confirmed").into(),
+            );
+        }
+    }
+    if args.deploy && !args.push && !args.chart_only {
+        warn!(
+            "--deploy without --push: the cluster must be able to pull {} itself",
+            image_name
+        );
+    }
+
+    // The bumped manifest goes into the copy of the project right away, but into the
+    // project home only after the run succeeded, so that a failed build changes nothing
+    let version_bump = match args.bump {
+        Some(ref part) => Some(bump_project_version(project_home, &project_type, part)?),
+        None => None,
+    };
+    let new_version = match version_bump {
+        Some(ref bump) => {
+            info!(
+                "Bumping the {} version in {}: {} -> {}",
+                args.bump.as_deref().unwrap(),
+                bump.file_name,
+                bump.old_version,
+                bump.new_version
+            );
+            Some(bump.new_version.clone())
+        }
+        None => args.project_version.clone(),
+    };
+    // The image is also tagged with the new version ('+' is not allowed in a tag)
+    if let Some(new_version) = new_version
+        && !args.chart_only
+    {
+        let version_ref = extra_image_ref(image_name, &new_version.replace('+', "_"))?;
+        if version_ref != *image_name && !extra_image_refs.contains(&version_ref) {
+            extra_image_refs.push(version_ref);
+        }
+    }
+    report.tags = extra_image_refs.clone();
+
+    if args.no_overwrite {
+        if args.force {
+            info!("--force: image {} is overwritten if it exists", image_name);
+        } else if local_image_exists(image_name)? {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "Image {} already exists locally (--no-overwrite). Use another \
+                     --image-name or add --force to overwrite it",
+                    image_name
+                ),
+            )
+            .into());
+        }
+    }
+
+    // Pin the base image to its current digest for reproducible builds
+    let pinned_base_image = if args.pin_base_image {
+        let pinned = resolve_base_image_digest(base_image)?;
+        info!("Pinned base image {} to {}", base_image, pinned);
+        Some(pinned)
+    } else {
+        None
+    };
+
+    // Everything is written to the temporary directory; --readonly-check verifies that
+    let project_snapshot = if args.readonly_check {
+        Some(snapshot_dir(project_home)?)
+    } else {
+        None
+    };
+
+    // Create temporary directory
+    // The random suffix keeps overlapping runs of the same project (e.g. in a shared PID
+    // namespace) apart; an existing directory is never reused, so no run clobbers another
+    let temp_dir = std::env::current_dir()?.join(format!(
+        "servicemaker-{}-{}-{}",
+        service_name,
+        std::process::id(),
+        random_suffix()
+    ));
+    debug!("Creating temporary directory: {}", temp_dir.display());
+    fs::create_dir(&temp_dir)?;
+
+    // helm package runs inside the temporary directory, so --chart-out is made absolute
+    let chart_out_dir = match args.chart_out {
+        Some(ref chart_out) => fs::create_dir_all(chart_out)
+            .and_then(|_| chart_out.canonicalize())
+            .map_err(|e| {
+                ServiceMakerError::new(
+                    ErrorKind::Config,
+                    format!("Failed to create the chart output directory: {}", e),
+                )
+                .with_context(chart_out.display().to_string())
+            })?,
+        None => temp_dir.clone(),
+    };
+
+    // Everything from here on runs inside the temporary directory, whose
+    // removal depends on --cleanup and on where the run failed
+    let progress = BuildOutcome::default();
+    let outcome = (|| -> Result<BuildArtifacts, Box<dyn std::error::Error>> {
+        // The build context is not needed when only the chart is generated
+        if !args.chart_only {
+            // Copy scripts to temp directory with executable permissions
+            copy_scripts_to_temp(&temp_dir)?;
+
+            // Copy project directory to temp directory
+            // Both Python and Node.js projects are copied directly without any special handling
+            let project_dest = temp_dir.join(initial_project_dir);
+            info!(
+                "Copying project from {} to {}",
+                project_home.display(),
+                project_dest.display()
+            );
+            let mut excludes = args.excludes.clone();
+            if !args.no_default_excludes {
+                excludes.extend(
+                    default_excludes(&project_type)
+                        .iter()
+                        .map(|exclude| exclude.to_string()),
+                );
+            }
+            if !excludes.is_empty() {
+                info!("Excluding from the build context: {}", excludes.join(", "));
+            }
+            copy_dir_recursive(
+                project_home,
+                &project_dest,
+                &CopyOptions {
+                    reflink: &args.reflink,
+                    follow_symlinks: args.follow_symlinks,
+                    excludes: &excludes,
+                },
+            )?;
+            if let Some(ref bump) = version_bump {
+                fs::write(project_dest.join(&bump.file_name), &bump.content)?;
+            }
+
+            // Build provenance for the service to report at runtime
+            if args.include_git_metadata {
+                let build_info = serde_json::json!({
+                    "git_sha": detect_git_commit(project_home),
+                    "build_time": format_utc_timestamp(std::time::SystemTime::now()),
+                    "servicemaker_version": env!("CARGO_PKG_VERSION"),
+                    "base_image": base_image,
+                    "base_image_digest": pinned_base_image
+                        .as_deref()
+                        .and_then(|pinned| pinned.split_once('@'))
+                        .map(|(_, digest)| digest),
+                });
+                let build_info_path = temp_dir.join("build-info.json");
+                fs::write(&build_info_path, serde_json::to_string_pretty(&build_info)?)?;
+                debug!("Created build info: {}", build_info_path.display());
+            }
+        }
+        let project_dir = initial_project_dir.to_string();
+
+        // Read environment variables from .env.example if it exists
+        let env_vars = read_env_example(project_home)?;
+        if !env_vars.is_empty() {
+            info!(
+                "Found {} environment variable(s) in .env.example",
+                env_vars.len()
+            );
+        }
+
+        // The service name is derived from the resolved project name above.
+        // The rest of the project metadata is read from the manifest here.
+        let mut metadata = match project_type.as_str() {
+            "python" => {
+                // Extract version from pyproject.toml or setup.cfg
+                let info = read_service_info_from_pyproject(project_home)
+                    .map_err(|e| ServiceMakerError::new(ErrorKind::Detection, e.to_string()))?;
+                let source = if project_home.join("pyproject.toml").exists() {
+                    "pyproject.toml"
+                } else if project_home.join("setup.cfg").exists() {
+                    "setup.cfg"
+                } else {
+                    "default for setup.py"
+                };
+                info!("Version from {}: {}", source, info.version);
+                info
+            }
+            "nodejs" => {
+                // Extract version from package.json
+                let info = read_service_info_from_package_json(project_home)
+                    .map_err(|e| ServiceMakerError::new(ErrorKind::Detection, e.to_string()))?;
+                info!("Version from package.json: {}", info.version);
+                info
+            }
+            "rust" => {
+                // Extract version from Cargo.toml
+                let info = read_service_info_from_cargo_toml(project_home)
+                    .map_err(|e| ServiceMakerError::new(ErrorKind::Detection, e.to_string()))?;
+                info!("Version from Cargo.toml: {}", info.version);
+                info
+            }
+            "deno" => {
+                // Extract version from deno.json
+                let info = read_service_info_from_deno_json(project_home)
+                    .map_err(|e| ServiceMakerError::new(ErrorKind::Detection, e.to_string()))?;
+                info!("Version from deno.json: {}", info.version);
+                info
+            }
+            "custom" => {
+                // Custom projects have no manifest to read from
+                info!("Version (default): {}", DEFAULT_CUSTOM_VERSION);
+                ProjectMetadata {
+                    name: name.clone(),
+                    version: DEFAULT_CUSTOM_VERSION.to_string(),
+                    description: None,
+                    maintainers: Vec::new(),
+                    license: None,
+                    homepage: None,
+                }
+            }
+            _ => {
+                return Err(ServiceMakerError::new(
+                    ErrorKind::Detection,
+                    "Unsupported project type for Helm chart generation",
+                )
+                .into());
+            }
+        };
+        if let Some(ref bump) = version_bump {
+            info!(
+                "Version from --bump: {} (instead of {})",
+                bump.new_version, metadata.version
+            );
+            metadata.version = bump.new_version.clone();
+        } else if let Some(ref project_version) = args.project_version {
+            info!(
+                "Version from --project-version: {} (instead of {})",
+                project_version, metadata.version
+            );
+            metadata.version = project_version.clone();
+        }
+        let version = metadata.version.clone();
+
+        // Image labels: OCI provenance labels first, so that --label can override them
+        let mut labels = vec![
+            ("org.opencontainers.image.title".to_string(), name.clone()),
+            (
+                "org.opencontainers.image.version".to_string(),
+                version.clone(),
+            ),
+        ];
+        if let Some(source) = detect_git_remote(project_home) {
+            labels.push(("org.opencontainers.image.source".to_string(), source));
+        }
+        if let Some(ref license) = metadata.license {
+            labels.push((
+                "org.opencontainers.image.licenses".to_string(),
+                license.clone(),
+            ));
+        }
+        for label in &args.labels {
+            labels.push(parse_key_value(label, "--label")?);
+        }
+
+        // Enforce the labeling policy before anything is built
+        let missing_labels: Vec<&str> = args
+            .require_labels
+            .iter()
+            .map(|label| label.trim())
+            .filter(|required| {
+                !required.is_empty() && !labels.iter().any(|(key, _)| key == required)
+            })
+            .collect();
+        if !missing_labels.is_empty() {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Config,
+                format!(
+                    "Missing required image label(s): {}. Add them with --label KEY=VALUE",
+                    missing_labels.join(", ")
+                ),
+            )
+            .into());
+        }
+
+        // Additional template placeholders: file first, so that --set can override entries
+        let mut template_vars = match args.template_vars_file {
+            Some(ref vars_file) => read_template_vars_file(vars_file)
+                .map_err(|e| ServiceMakerError::new(ErrorKind::Config, e.to_string()))?,
+            None => Vec::new(),
+        };
+        for entry in &args.set {
+            let (key, value) = parse_key_value(entry, "--set")?;
+            template_vars.retain(|(existing, _)| *existing != key);
+            template_vars.push((key, value));
+        }
+
+        let dockerfile_params = DockerfileParams {
+            base_image: pinned_base_image.as_deref().unwrap_or(base_image),
+            project_dir: &project_dir,
+            entrypoint: args.entrypoint.as_deref().unwrap_or(""),
+            port,
+            env_vars: &env_vars,
+            labels: &labels,
+            health_path: args.health_path.as_deref(),
+            build_info: args.include_git_metadata && !args.chart_only,
+        };
+
+        let python_version = (project_type == "python").then(|| {
+            resolve_python_version(args.python_version.as_deref(), project_home, base_image)
+        });
+
+        // Choose Dockerfile template and modify based on project type
+        let modified_dockerfile = match project_type.as_str() {
+            // A user-supplied Dockerfile template replaces the built-in ones
+            _ if args.dockerfile.is_some() => {
+                let dockerfile = args.dockerfile.as_ref().unwrap();
+                let dockerfile_template = fs::read_to_string(dockerfile).map_err(|e| {
+                    ServiceMakerError::new(
+                        ErrorKind::Config,
+                        format!(
+                            "Failed to read Dockerfile template {}: {}",
+                            dockerfile.display(),
+                            e
+                        ),
+                    )
+                })?;
+                info!("Using Dockerfile template {}", dockerfile.display());
+                modify_dockerfile_user(
+                    &dockerfile_template,
+                    &dockerfile_params,
+                    &project_type,
+                    python_version.as_deref(),
+                )
+            }
+            "python" => {
+                // Python project: use Python Dockerfile template
+                modify_dockerfile_python(
+                    DOCKERFILE_TEMPLATE,
+                    &dockerfile_params,
+                    python_version.as_deref().unwrap(),
+                )
+            }
+            "nodejs" if args.runtime.as_deref() == Some("bun") => {
+                // Node.js project on Bun: use the Bun Dockerfile template
+                modify_dockerfile_bun(BUN_DOCKERFILE_TEMPLATE, &dockerfile_params)
+            }
+            "nodejs" => {
+                // Node.js project: use Node.js Dockerfile template
+                let package_manager = args.package_manager.as_deref().unwrap_or("npm");
+                modify_dockerfile_nodejs(
+                    DOCKERFILE_TEMPLATE,
+                    &dockerfile_params,
+                    package_manager_install(project_home, package_manager).as_deref(),
+                    args.out_dir
+                        .as_ref()
+                        .and(args.build_script.as_deref())
+                        .map(|script| package_manager_build(project_home, package_manager, script)),
+                )
+            }
+            "rust" => {
+                // Rust project: use the multi-stage Rust Dockerfile template
+                modify_dockerfile_rust(RUST_DOCKERFILE_TEMPLATE, &dockerfile_params)
+            }
+            "deno" => {
+                // Deno project: use the Deno Dockerfile template
+                modify_dockerfile_deno(DENO_DOCKERFILE_TEMPLATE, &dockerfile_params)
+            }
+            _ => {
+                return Err(ServiceMakerError::new(
+                    ErrorKind::Detection,
+                    "Unsupported project type",
+                )
+                .into());
+            }
+        }
+        .map_err(|e| {
+            ServiceMakerError::new(
+                ErrorKind::Config,
+                format!("Failed to render Dockerfile template: {}", e),
+            )
+        })?;
+
+        // Warn about template variables which would not be substituted anywhere
+        for (key, _) in &template_vars {
+            let placeholder = format!("{{{}}}", key);
+            if !modified_dockerfile.contains(&placeholder)
+                && !chart_files
+                    .iter()
+                    .any(|(_, content)| content.contains(&placeholder))
+            {
+                warn!(
+                    "template variable '{}' does not match any placeholder in the templates",
+                    key
+                );
+            }
+        }
+        let modified_dockerfile = substitute_template_vars(&modified_dockerfile, &template_vars);
+
+        // Write modified Dockerfile to temp directory
+        let dockerfile_path = temp_dir.join("Dockerfile");
+        fs::write(&dockerfile_path, &modified_dockerfile)?;
+        debug!("Created Dockerfile: {}", dockerfile_path.display());
+        if let Some(ref dockerfile_out) = args.dockerfile_out {
+            fs::write(dockerfile_out, &modified_dockerfile).map_err(|e| {
+                ServiceMakerError::new(
+                    ErrorKind::Config,
+                    format!("Failed to write the Dockerfile: {}", e),
+                )
+                .with_context(dockerfile_out.display().to_string())
+            })?;
+            info!("✓ Dockerfile written to {}", dockerfile_out.display());
+        }
+
+        let inputs_hash = if args.skip_if_exists {
+            Some(build_inputs_hash(&temp_dir, &project_dir)?)
+        } else {
+            None
+        };
+
+        // Record the pinned base image next to the Dockerfile
+        if let Some(ref pinned) = pinned_base_image {
+            let lock_path = temp_dir.join("base-image.lock");
+            let mut lock = toml::Table::new();
+            lock.insert("base_image".to_string(), base_image.clone().into());
+            lock.insert("pinned".to_string(), pinned.clone().into());
+            fs::write(&lock_path, toml::to_string(&lock)?)?;
+            info!("Recorded base image digest in {}", lock_path.display());
+        }
+
+        let chart_dir = temp_dir.join(&service_name);
+        let chart_params = ChartParams {
+            service_name: &service_name,
+            chart_version: args.chart_version.as_deref().unwrap_or(&version),
+            chart_type: &args.chart_type,
+            metadata: &metadata,
+            port,
+            image_name,
+            health_path: args.health_path.as_deref(),
+            replicas: args.replicas,
+            service_type: &args.service_type,
+            node_port: args.node_port,
+            ingress_host: args.ingress_host.as_deref(),
+            ingress_class: args.ingress_class.as_deref(),
+            cpu_request: args.cpu_request.as_deref(),
+            cpu_limit: args.cpu_limit.as_deref(),
+            memory_request: args.memory_request.as_deref(),
+            memory_limit: args.memory_limit.as_deref(),
+            env: &deployment_env,
+            config_map: &config_map,
+            pull_secrets: &args.pull_secrets,
+            annotations: &annotations,
+            k8s_labels: &k8s_labels,
+            environments: &args.environments,
+            chart_files: &chart_files,
+            variants: &chart_variants,
+            template_vars: &template_vars,
+        };
+
+        let manifests_path = args
+            .render
+            .then(|| temp_dir.join(format!("{}-manifests.yaml", service_name)));
+        let tar_gz_path = match args.tar_output {
+            Some(ref tar_output) => std::path::absolute(tar_output)?,
+            None => temp_dir.join("project.tar.gz"),
+        };
+        // Only a Python project without dependencies of its own has nothing in the
+        // image that the archive needs: its virtual environment stays empty
+        let tar_local = args.tar_local
+            && args.make_tar_gz
+            && if project_type == "python" && !pyproject_has_dependencies(project_home) {
+                true
+            } else {
+                info!(
+                    "--tar-local: the archive needs files from the image, \
+                    creating it in a container"
+                );
+                false
+            };
+        let tar_gz_params = TarGzParams {
+            tar_file_path: &tar_gz_path,
+            temp_dir: &temp_dir,
+            image_name,
+            project_dir: &project_dir,
+            entrypoint: args.entrypoint.as_deref().unwrap_or(""),
+            tar_output_path: &args.tar_output_path,
+            run_as_current_user: args.run_as_current_user,
+            timeout: args.timeout.map(Duration::from_secs),
+            local: tar_local,
+        };
+
+        // The tar.gz needs the built image and therefore runs after the build
+        let build_and_archive = || -> Result<(), Box<dyn std::error::Error>> {
+            build_and_publish_image(
+                &args,
+                &temp_dir,
+                image_name,
+                &extra_image_refs,
+                inputs_hash.as_deref(),
+                registry_login.as_ref(),
+                &progress,
+            )?;
+            if args.make_tar_gz {
+                create_tar_gz(&tar_gz_params)?;
+                BuildOutcome::mark(&progress.tar_gz_created);
+            }
+            Ok(())
+        };
+        let generate_chart = || {
+            generate_helm_chart(
+                &temp_dir,
+                &chart_out_dir,
+                &chart_dir,
+                &chart_params,
+                manifests_path.as_deref(),
+                args.render_namespace.as_deref(),
+                args.strict_lint,
+            )
+            .inspect(|_| BuildOutcome::mark(&progress.chart_packaged))
+        };
+        // An image is only published with a valid chart: unless --push-anyway, the chart
+        // is generated and linted before the image is built and pushed
+        let chart_first = (args.push || args.platform.is_some()) && !args.push_anyway;
+
+        let chart_file_paths = if args.chart_only {
+            generate_chart()?
+        } else if args.no_helm {
+            build_and_archive()?;
+            Vec::new()
+        } else if chart_first {
+            let chart_file_paths = generate_chart()?;
+            build_and_archive()?;
+            chart_file_paths
+        } else {
+            // The chart only references the image by name, so it is generated, linted
+            // and packaged while the image builds
+            std::thread::scope(
+                |scope| -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+                    let chart_handle = scope.spawn(generate_chart);
+                    let build_result = build_and_archive();
+
+                    let chart_result = chart_handle.join().map_err(|_| {
+                        ServiceMakerError::new(
+                            ErrorKind::Internal,
+                            "Helm chart generation thread panicked",
+                        )
+                    })?;
+
+                    build_result?;
+                    Ok(chart_result?)
+                },
+            )?
+        };
+
+        if !args.no_checksum {
+            for artifact in chart_file_paths
+                .iter()
+                .chain(args.make_tar_gz.then_some(&tar_gz_path))
+            {
+                let digest = write_checksum(artifact)?;
+                status!(
+                    "SHA-256 of {}: {}",
+                    artifact.file_name().unwrap().to_string_lossy(),
+                    digest
+                );
+            }
+        }
+
+        // Push the chart last, so that it never references an image which failed to build
+        if let Some(ref chart_registry) = args.chart_registry {
+            for chart_file_path in &chart_file_paths {
+                push_helm_chart(chart_file_path, chart_registry)?;
+            }
+            BuildOutcome::mark(&progress.chart_pushed);
+        }
+
+        if args.deploy {
+            deploy_helm_chart(
+                &chart_dir,
+                args.release_name.as_deref().unwrap(),
+                args.namespace.as_deref().unwrap(),
+            )?;
+            BuildOutcome::mark(&progress.deployed);
+        }
+
+        Ok(BuildArtifacts {
+            charts: chart_file_paths,
+            manifests: manifests_path,
+            tar_gz: args.make_tar_gz.then_some(tar_gz_path),
+        })
+    })();
+
+    let outcome = match project_snapshot {
+        Some(ref before) => outcome.and_then(|artifacts| {
+            check_unchanged(project_home, before)?;
+            info!("✓ Project home is unchanged");
+            Ok(artifacts)
+        }),
+        None => outcome,
+    };
+
+    let outcome = match version_bump {
+        Some(ref bump) => outcome.and_then(|artifacts| {
+            fs::write(&bump.path, &bump.content).map_err(|e| {
+                ServiceMakerError::new(
+                    ErrorKind::Config,
+                    format!("Failed to write {}: {}", bump.file_name, e),
+                )
+                .with_context(bump.path.display().to_string())
+            })?;
+            status!(
+                "Bumped the {} version in {}: {} -> {}",
+                args.bump.as_deref().unwrap(),
+                bump.file_name,
+                bump.old_version,
+                bump.new_version
+            );
+            Ok(artifacts)
+        }),
+        None => outcome,
+    };
+
+    let failure = outcome
+        .as_ref()
+        .err()
+        .map(|e| ServiceMakerError::from_error(e.as_ref()).kind);
+    if let Some(failure) = failure
+        && args.summary_on_failure
+    {
+        progress.print_summary(&args, failure);
+    }
+    report.steps = progress
+        .phase_statuses(&args, failure)
+        .into_iter()
+        .map(|(key, _, status)| (key, status))
+        .collect();
+    if !should_cleanup(&args.cleanup, failure) {
+        let artifacts = outcome?;
+        report.charts = artifacts.charts.clone();
+        report.manifests = artifacts.manifests.clone();
+        report.tar_gz = artifacts.tar_gz.clone();
+        if !artifacts.charts.is_empty() {
+            status!();
+        }
+        for chart_file_path in &artifacts.charts {
+            // Outside the temporary directory the chart is reported with its full path
+            if args.chart_out.is_some() {
+                status!("Generated Helm chart: {}", chart_file_path.display());
+            } else {
+                let chart_file_name = chart_file_path.file_name().unwrap().to_string_lossy();
+                status!("Generated Helm chart: {}", chart_file_name);
+            }
+        }
+        if let Some(manifests_path) = artifacts.manifests {
+            let manifests_file_name = manifests_path.file_name().unwrap().to_string_lossy();
+            status!("Rendered manifests: {}", manifests_file_name);
+        }
+        if let Some(ref pinned) = pinned_base_image {
+            status!("Base image digest: {}", pinned);
+        }
+
+        status!("\nTemporary directory: {}", temp_dir.display());
+        status!("(Note: Temporary directory is left behind for inspection)");
+        return Ok(());
+    }
+
+    // Keep the artifacts by moving them out of the temporary directory first
+    if let Ok(ref artifacts) = outcome {
+        for artifact in artifacts.charts.iter().chain(
+            [&artifacts.manifests, &artifacts.tar_gz]
+                .into_iter()
+                .flatten(),
+        ) {
+            // --tar-output already placed the archive outside of the temporary directory
+            let target = if artifact.starts_with(&temp_dir) {
+                let target = std::env::current_dir()?.join(artifact.file_name().unwrap());
+                fs::rename(artifact, &target)?;
+                let checksum = checksum_path(artifact);
+                if checksum.exists() {
+                    fs::rename(&checksum, checksum_path(&target))?;
+                }
+                target
+            } else {
+                artifact.clone()
+            };
+            status!("\nGenerated: {}", target.display());
+            if artifacts.charts.contains(artifact) {
+                report.charts.push(target);
+            } else if artifacts.manifests.as_ref() == Some(artifact) {
+                report.manifests = Some(target);
+            } else {
+                report.tar_gz = Some(target);
+            }
+        }
+        if let Some(ref pinned) = pinned_base_image {
+            status!("Base image digest: {}", pinned);
+        }
+    }
+
+    fs::remove_dir_all(&temp_dir)?;
+    info!("Removed temporary directory: {}", temp_dir.display());
+    outcome.map(|_| ())
+}
+
+/// Image label holding the hash of the build inputs, for --skip-if-exists
+const INPUTS_HASH_LABEL: &str = "org.arangodb.servicemaker.inputs-hash";
+
+/// SHA-256 of what goes into the image: the rendered Dockerfile, the scripts and the
+/// copied project. The files are walked in sorted order with their relative paths and
+/// permissions, so the hash only depends on their contents.
+fn build_inputs_hash(temp_dir: &Path, project_dir: &str) -> io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    fn visit(root: &Path, path: &Path, hasher: &mut Sha256) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(path)?;
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        hasher.update(relative.as_os_str().as_encoded_bytes());
+        hasher.update([0]);
+        hasher.update(metadata.permissions().mode().to_le_bytes());
+        if metadata.is_dir() {
+            let mut entries = fs::read_dir(path)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<io::Result<Vec<_>>>()?;
+            entries.sort();
+            for entry in entries {
+                visit(root, &entry, hasher)?;
+            }
+        } else if metadata.file_type().is_symlink() {
+            hasher.update(fs::read_link(path)?.as_os_str().as_encoded_bytes());
+        } else {
+            let content = fs::read(path)?;
+            hasher.update((content.len() as u64).to_le_bytes());
+            hasher.update(content);
+        }
+        Ok(())
+    }
+
+    let mut hasher = Sha256::new();
+    for input in ["Dockerfile", "scripts", project_dir] {
+        visit(temp_dir, &temp_dir.join(input), &mut hasher)?;
+    }
+    Ok(hex_digest(&hasher.finalize()))
+}
+
+/// The value of `label` on the local image, if the image exists and has it
+fn image_label(image_name: &str, label: &str) -> Option<String> {
+    let output = Command::new("docker")
+        .args([
+            "image",
+            "inspect",
+            "--format",
+            &format!("{{{{index .Config.Labels \"{}\"}}}}", label),
+            image_name,
+        ])
+        .logged()
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!value.is_empty() && value != "<no value>").then_some(value)
+}
+
+/// Lowercase hex of a digest, as printed by sha256sum
+fn hex_digest(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The checksum file written next to `artifact`, e.g. project.tar.gz.sha256
+fn checksum_path(artifact: &Path) -> PathBuf {
+    let mut path = artifact.as_os_str().to_owned();
+    path.push(".sha256");
+    PathBuf::from(path)
+}
+
+/// Write the SHA-256 of `artifact` to its checksum file, in the format of
+/// `sha256sum` so that `sha256sum -c` can verify it. Returns the hex digest
+fn write_checksum(artifact: &Path) -> io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let hex = hex_digest(&Sha256::digest(fs::read(artifact)?));
+    fs::write(
+        checksum_path(artifact),
+        format!(
+            "{}  {}\n",
+            hex,
+            artifact.file_name().unwrap().to_string_lossy()
+        ),
+    )?;
+    Ok(hex)
+}
+
+/// Push a packaged chart to an OCI registry with `helm push`
+/// Uses the credentials of a previous `helm registry login`.
+fn push_helm_chart(chart_file_path: &Path, chart_registry: &str) -> Result<(), ServiceMakerError> {
+    info!("\nPushing Helm chart to {}...", chart_registry);
+    let push_status = Command::new("helm")
+        .args(["push", chart_file_path.to_str().unwrap(), chart_registry])
+        .stdout(child_stdout())
+        .logged()
+        .status()
+        .map_err(|e| {
+            ServiceMakerError::new(
+                ErrorKind::Tooling,
+                format!("Failed to run helm push: {}", e),
+            )
+        })?;
+
+    if !push_status.success() {
+        return Err(
+            ServiceMakerError::new(ErrorKind::ChartPush, "Helm chart push failed")
+                .with_context(chart_registry),
+        );
+    }
+
+    info!("✓ Helm chart pushed successfully to {}", chart_registry);
+    Ok(())
+}
+
+/// Install or upgrade `release_name` with the chart in `chart_dir` in the current kube-context
+fn deploy_helm_chart(
+    chart_dir: &Path,
+    release_name: &str,
+    namespace: &str,
+) -> Result<(), ServiceMakerError> {
+    info!(
+        "\nDeploying release {} to namespace {}...",
+        release_name, namespace
+    );
+    let deploy_status = Command::new("helm")
+        .args([
+            "upgrade",
+            "--install",
+            release_name,
+            chart_dir.to_str().unwrap(),
+            "--namespace",
+            namespace,
+        ])
+        .stdout(child_stdout())
+        .logged()
+        .status()
+        .map_err(|e| {
+            ServiceMakerError::new(
+                ErrorKind::Tooling,
+                format!("Failed to run helm upgrade: {}", e),
+            )
+        })?;
+
+    if !deploy_status.success() {
+        return Err(
+            ServiceMakerError::new(ErrorKind::Deploy, "Helm upgrade --install failed")
+                .with_context(release_name),
+        );
+    }
+
+    status!(
+        "✓ Release {} deployed to namespace {}",
+        release_name,
+        namespace
+    );
+    Ok(())
+}
+
+/// Render the chart with `helm template` and write the manifests to a file
+fn render_helm_chart(
+    chart_dir: &Path,
+    manifests_path: &Path,
+    namespace: Option<&str>,
+) -> Result<(), ServiceMakerError> {
+    info!("\nRunning helm template...");
+    let mut template_command = Command::new("helm");
+    template_command.args(["template", chart_dir.to_str().unwrap()]);
+    if let Some(namespace) = namespace {
+        template_command.args(["--namespace", namespace]);
+    }
+    let output = template_command.logged().output().map_err(|e| {
+        ServiceMakerError::new(
+            ErrorKind::Tooling,
+            format!("Failed to run helm template: {}", e),
+        )
+    })?;
+
+    if !output.status.success() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::HelmTemplate,
+            format!(
+                "Helm template failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        )
+        .with_context(chart_dir.display().to_string()));
+    }
+
+    fs::write(manifests_path, &output.stdout).map_err(|e| {
+        ServiceMakerError::new(
+            ErrorKind::HelmTemplate,
+            format!("Failed to write rendered manifests: {}", e),
+        )
+        .with_context(manifests_path.display().to_string())
+    })?;
+    info!(
+        "✓ Rendered manifests written to {}",
+        manifests_path.display()
+    );
+    Ok(())
+}
+
+/// Decide whether the temporary directory is removed for the given --cleanup
+/// policy, after a successful run or a failure of the given kind
+fn should_cleanup(policy: &str, failure: Option<ErrorKind>) -> bool {
+    matches!(
+        (policy, failure),
+        ("always", _)
+            | ("on-success" | "on-success-and-lint-failure", None)
+            | ("on-success-and-lint-failure", Some(ErrorKind::HelmLint))
+    )
+}
+
+/// Build the Docker image and push it if requested
+fn build_and_publish_image(
+    args: &Args,
+    temp_dir: &Path,
+    image_name: &str,
+    extra_image_refs: &[String],
+    inputs_hash: Option<&str>,
+    registry_login: Option<&RegistryLogin>,
+    progress: &BuildOutcome,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Log in first: a multi-platform build pushes as part of the build
+    if let Some(login) = registry_login {
+        docker_login(image_registry(image_name), login)?;
+    }
+
+    // --skip-if-exists: the hash of the inputs of the existing image is in its label
+    let up_to_date = inputs_hash
+        .is_some_and(|hash| image_label(image_name, INPUTS_HASH_LABEL).as_deref() == Some(hash));
+    if up_to_date {
+        status!(
+            "\n✓ Docker image {} is up to date (build inputs unchanged), skipping the build",
+            image_name
+        );
+        BuildOutcome::mark(&progress.image_built);
+    } else {
+        info!("\nBuilding Docker image...");
+        let mut build_command = Command::new("docker");
+        if let Some(ref platform) = args.platform {
+            // Multi-arch build: buildx pushes the image as part of the build
+            build_command.args([
+                "buildx",
+                "build",
+                "--platform",
+                platform,
+                "--push",
+                "-f",
+                "./Dockerfile",
+                "-t",
+                image_name,
+            ]);
+            for image_ref in extra_image_refs {
+                build_command.args(["-t", image_ref]);
+            }
+            build_command.arg(".");
+        } else {
+            build_command.args(["build", "-f", "./Dockerfile", "-t", image_name]);
+            if let Some(hash) = inputs_hash {
+                build_command.args(["--label", &format!("{}={}", INPUTS_HASH_LABEL, hash)]);
+            }
+            build_command.arg(".");
+        }
+        let timeout = args.timeout.map(Duration::from_secs);
+        let mut build_child = build_command
+            .current_dir(temp_dir)
+            .stdout(child_stdout())
+            .logged()
+            .spawn()?;
+        let Some(build_status) = wait_with_timeout(&mut build_child, timeout)? else {
+            return Err(timeout_error("Docker build", timeout, image_name).into());
+        };
+
+        if !build_status.success() {
+            return Err(
+                ServiceMakerError::new(ErrorKind::DockerBuild, "Docker build failed")
+                    .with_context(image_name)
+                    .into(),
+            );
+        }
+
+        status!("\n✓ Docker image built successfully: {}", image_name);
+        BuildOutcome::mark(&progress.image_built);
+    }
+
+    // buildx applies all tags during the build, a local build is tagged afterwards
+    if args.platform.is_none() {
+        for image_ref in extra_image_refs {
+            let tag_status = Command::new("docker")
+                .args(["tag", image_name, image_ref])
+                .stdout(child_stdout())
+                .logged()
+                .status()?;
+            if !tag_status.success() {
+                return Err(
+                    ServiceMakerError::new(ErrorKind::DockerBuild, "Docker tag failed")
+                        .with_context(image_ref.as_str())
+                        .into(),
+                );
+            }
+            info!("✓ Tagged image as {}", image_ref);
+        }
+    }
+
+    // Push Docker image if requested
+    if args.platform.is_some() {
+        info!("✓ Multi-platform Docker image pushed during build");
+        BuildOutcome::mark(&progress.image_pushed);
+    } else if args.push {
+        for image_ref in
+            std::iter::once(image_name).chain(extra_image_refs.iter().map(String::as_str))
+        {
+            info!("\nPushing Docker image {}...", image_ref);
+            let outcome = run_with_retries(args.push_retries, || {
+                let mut push_command = Command::new("docker");
+                push_command.args(["push", image_ref]);
+                push_command
+            })?;
+
+            let message = match outcome {
+                RetryOutcome::Succeeded => continue,
+                RetryOutcome::AuthFailed => {
+                    "Docker push failed with an authentication error (not retried); \
+                    check your docker login"
+                        .to_string()
+                }
+                RetryOutcome::Exhausted { attempts } => {
+                    format!(
+                        "Docker push failed, giving up after {} attempt(s)",
+                        attempts
+                    )
+                }
+            };
+            return Err(ServiceMakerError::new(ErrorKind::DockerPush, message)
+                .with_context(image_ref)
+                .into());
+        }
+
+        info!("✓ Docker image pushed successfully");
+        BuildOutcome::mark(&progress.image_pushed);
+    }
+
+    // Only a pushed image has a registry digest
+    let pushed = args.push || args.platform.is_some();
+    let digest = pushed
+        .then(|| resolve_image_digest(image_name, args.platform.is_some()))
+        .flatten();
+    if let Some(ref digest) = digest {
+        let pinned = format!("{}@{}", image_repository(image_name), digest);
+        status!("Image digest: {}", pinned);
+        if let Some(ref digest_file) = args.digest_file {
+            fs::write(digest_file, format!("{}\n", pinned)).map_err(|e| {
+                ServiceMakerError::new(
+                    ErrorKind::Config,
+                    format!("Failed to write image digest: {}", e),
+                )
+                .with_context(digest_file.display().to_string())
+            })?;
+            info!("✓ Image digest written to {}", digest_file.display());
+        }
+    } else if pushed {
+        warn!("could not resolve the digest of {}", image_name);
+        if args.digest_file.is_some() {
+            return Err(ServiceMakerError::new(
+                ErrorKind::DockerPush,
+                "Could not resolve the digest of the pushed image for --digest-file",
+            )
+            .with_context(image_name)
+            .into());
+        }
+    }
+
+    if let Some(ref image_ref_file) = args.image_ref_file {
+        let image_ref = match digest {
+            Some(digest) => format!("{}@{}", image_name, digest),
+            None => image_name.to_string(),
+        };
+        fs::write(image_ref_file, format!("{}\n", image_ref)).map_err(|e| {
+            ServiceMakerError::new(
+                ErrorKind::Config,
+                format!("Failed to write image reference: {}", e),
+            )
+            .with_context(image_ref_file.display().to_string())
+        })?;
+        info!(
+            "✓ Image reference {} written to {}",
+            image_ref,
+            image_ref_file.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Log in to `registry` with `docker login`, passing the password on stdin so
+/// that it never appears on a command line or in the output
+fn docker_login(registry: &str, login: &RegistryLogin) -> Result<(), ServiceMakerError> {
+    info!("\nLogging in to {} as {}...", registry, login.user);
+    let mut login_command = Command::new("docker");
+    login_command.args(["login", "--username", &login.user, "--password-stdin"]);
+    // Docker Hub is the default server of docker login
+    if registry != "docker.io" {
+        login_command.arg(registry);
+    }
+    let mut child = login_command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .logged()
+        .spawn()
+        .map_err(|e| {
+            ServiceMakerError::new(
+                ErrorKind::Tooling,
+                format!("Failed to run docker login: {}", e),
+            )
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A write error shows up as a failed login below
+        let _ = stdin.write_all(login.password.0.as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|e| {
+        ServiceMakerError::new(
+            ErrorKind::Tooling,
+            format!("Failed to run docker login: {}", e),
+        )
+    })?;
+
+    if !output.status.success() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::RegistryLogin,
+            format!(
+                "Docker login failed, not pushing: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        )
+        .with_context(registry));
+    }
+
+    info!("✓ Logged in to {}", registry);
+    Ok(())
+}
+
+/// Read a password from stdin (up to the first newline) without echoing it
+fn read_password_from_stdin() -> Result<Secret, ServiceMakerError> {
+    // Typed input is hidden with stty; piped input is never echoed anyway
+    let terminal = io::stdin().is_terminal();
+    if terminal {
+        eprint!("Registry password: ");
+        let _ = Command::new("stty").arg("-echo").logged().status();
+    }
+    let mut password = String::new();
+    let read = io::stdin().read_line(&mut password);
+    if terminal {
+        let _ = Command::new("stty").arg("echo").logged().status();
+        eprintln!();
+    }
+    read.map_err(|e| {
+        ServiceMakerError::new(
+            ErrorKind::Config,
+            format!("Failed to read the registry password from stdin: {}", e),
+        )
+    })?;
+    let password = password.trim_end_matches(['\r', '\n']).to_string();
+    if password.is_empty() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            "--registry-password-stdin: no password on stdin",
+        ));
+    }
+    Ok(Secret(password))
+}
+
+/// Result of a command run with [`run_with_retries`]
+enum RetryOutcome {
+    Succeeded,
+    /// The command failed with an error that retrying cannot fix
+    AuthFailed,
+    /// The command failed on every attempt
+    Exhausted {
+        attempts: u32,
+    },
+}
+
+/// Run a command, retrying it up to `retries` times with exponential backoff
+/// (1s, 2s, 4s, ... capped at 30s) while it fails. Its stderr is passed through
+/// and checked for authentication errors, which are not retried.
+fn run_with_retries(
+    retries: u32,
+    mut make_command: impl FnMut() -> Command,
+) -> io::Result<RetryOutcome> {
+    let attempts = retries + 1;
+    for attempt in 1..=attempts {
+        let mut child = make_command()
+            .stdout(child_stdout())
+            .stderr(Stdio::piped())
+            .logged()
+            .spawn()?;
+        let mut stderr = String::new();
+        if let Some(pipe) = child.stderr.take() {
+            for line in io::BufReader::new(pipe).lines() {
+                let line = line?;
+                eprintln!("{}", line);
+                stderr.push_str(&line);
+                stderr.push('\n');
+            }
+        }
+        if child.wait()?.success() {
+            return Ok(RetryOutcome::Succeeded);
+        }
+
+        let stderr = stderr.to_lowercase();
+        if [
+            "unauthorized",
+            "authentication required",
+            "access to the resource is denied",
+        ]
+        .iter()
+        .any(|marker| stderr.contains(marker))
+        {
+            return Ok(RetryOutcome::AuthFailed);
+        }
+
+        if attempt < attempts {
+            let delay = 1u64 << (attempt - 1).min(5);
+            let delay = delay.min(30);
+            warn!(
+                "Attempt {}/{} failed, retrying in {}s...",
+                attempt, attempts, delay
+            );
+            std::thread::sleep(std::time::Duration::from_secs(delay));
+        }
+    }
+    Ok(RetryOutcome::Exhausted { attempts })
+}
+
+/// Look up the registry digest (`sha256:...`) of a pushed image
+/// Multi-platform images only exist in the registry, so they are inspected there.
+fn resolve_image_digest(image_name: &str, multi_platform: bool) -> Option<String> {
+    let output = if multi_platform {
+        Command::new("docker")
+            .args([
+                "buildx",
+                "imagetools",
+                "inspect",
+                "--format",
+                "{{.Manifest.Digest}}",
+                image_name,
+            ])
+            .logged()
+            .output()
+    } else {
+        Command::new("docker")
+            .args([
+                "inspect",
+                "--format",
+                "{{range .RepoDigests}}{{println .}}{{end}}",
+                image_name,
+            ])
+            .logged()
+            .output()
+    }
+    .ok()
+    .filter(|output| output.status.success())?;
+
+    // RepoDigests lists repo@sha256:... for every repository the image was pushed to
+    let repository = image_repository(image_name);
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find_map(|line| match line.split_once('@') {
+            Some((repo, digest)) if repo == repository => Some(digest),
+            Some(_) => None,
+            None => Some(line),
+        })
+        .filter(|digest| digest.starts_with("sha256:"))
+        .map(str::to_string)
+}
+
+/// The full image reference for a --tag value: a plain tag applies to the
+/// repository of `image_name`, anything with a `/` or `:` is a reference of its own
+fn extra_image_ref(image_name: &str, tag: &str) -> Result<String, ServiceMakerError> {
+    let image_ref = if tag.contains(['/', ':']) {
+        tag.to_string()
+    } else {
+        format!("{}:{}", image_repository(image_name), tag)
+    };
+
+    let repository = image_repository(&image_ref);
+    // A reference without a tag gets Docker's default tag "latest"
+    let tag_part = image_ref.get(repository.len() + 1..);
+    let valid_tag = |t: Option<&str>| {
+        let Some(t) = t else {
+            return true;
+        };
+        !t.is_empty()
+            && t.len() <= 128
+            && !t.starts_with(['.', '-'])
+            && t.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c))
+    };
+    // Registry hosts may contain upper case letters and a port, path components may not
+    let (host, path) = match repository.split_once('/') {
+        Some((host, path)) if host.contains(['.', ':']) || host == "localhost" => (host, path),
+        _ => ("", repository),
+    };
+    let valid_repository = !path.is_empty()
+        && path.split('/').all(|component| {
+            !component.is_empty()
+                && component
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "._-".contains(c))
+        })
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ".-:".contains(c));
+
+    if image_ref.contains('@') || !valid_repository || !valid_tag(tag_part) {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!(
+                "Invalid --tag '{}': expected a tag such as 1.2.3 or an image reference such as registry.example.com/app:1.2.3",
+                tag
+            ),
+        ));
+    }
+    Ok(image_ref)
+}
+
+/// Strip the tag (and digest) from an image reference, e.g. `registry:5000/app:1.0` -> `registry:5000/app`
+fn image_repository(image_name: &str) -> &str {
+    let image_name = image_name.split('@').next().unwrap_or(image_name);
+    match image_name.rfind(':') {
+        Some(colon) if !image_name[colon..].contains('/') => &image_name[..colon],
+        _ => image_name,
+    }
+}
+
+/// List what the zipper container added below `dir`, to show whether the archive was
+/// written at all. The container has already exited, so `docker exec` is not possible and
+/// the changes of its filesystem are listed with `docker diff` instead.
+fn container_dir_listing(container_id: &str, dir: &str) -> String {
+    let prefix = format!(" {}/", dir.trim_end_matches('/'));
+    match Command::new("docker")
+        .args(["diff", container_id])
+        .logged()
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            let listing = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| line.contains(&prefix))
+                .map(|line| format!("  {}", line))
+                .collect::<Vec<_>>()
+                .join("\n");
+            if listing.is_empty() {
+                "  (none)".to_string()
+            } else {
+                listing
+            }
+        }
+        Ok(output) => format!(
+            "  (docker diff failed: {})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => format!("  (failed to run docker diff: {})", e),
+    }
+}
+
+/// A started container, which is force-removed when dropped unless `remove`
+/// succeeded, so that it does not outlive a failed or timed out run
+struct Container {
+    id: String,
+    removed: bool,
+}
+
+impl Container {
+    fn remove(mut self) -> Result<(), ServiceMakerError> {
+        debug!("Removing container...");
+        let rm_status = Command::new("docker")
+            .args(["rm", &self.id])
+            .stdout(child_stdout())
+            .logged()
+            .status()
+            .map_err(|e| ServiceMakerError::new(ErrorKind::TarGz, e.to_string()))?;
+        if !rm_status.success() {
+            return Err(
+                ServiceMakerError::new(ErrorKind::TarGz, "Failed to remove container")
+                    .with_context(self.id.as_str()),
+            );
+        }
+        self.removed = true;
+        Ok(())
+    }
+}
+
+impl Drop for Container {
+    fn drop(&mut self) {
+        if !self.removed {
+            debug!("Force-removing container {}", self.id);
+            let removed = Command::new("docker")
+                .args(["rm", "-f", &self.id])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .logged()
+                .status()
+                .is_ok_and(|status| status.success());
+            if !removed {
+                warn!(
+                    "Failed to remove container {}, remove it with: docker rm -f {}",
+                    self.id, self.id
+                );
+            }
+        }
+    }
+}
+
+/// Wait for `child` to exit, killing it once `timeout` has passed. Returns `None`
+/// if it was killed
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> io::Result<Option<ExitStatus>> {
+    let Some(timeout) = timeout else {
+        return child.wait().map(Some);
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn timeout_error(what: &str, timeout: Option<Duration>, context: &str) -> ServiceMakerError {
+    ServiceMakerError::new(
+        ErrorKind::Timeout,
+        format!(
+            "{} did not finish within {}s (--timeout)",
+            what,
+            timeout.unwrap_or_default().as_secs()
+        ),
+    )
+    .with_context(context)
+}
+
+/// Run zipper.sh in a detached container of the built image, optionally as the
+/// given `uid:gid`, and wait for it. Returns the container and its exit code.
+fn run_zipper_container(
+    image_name: &str,
+    project_dir: &str,
+    tar_output_path: &str,
+    user: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<(Container, i32), Box<dyn std::error::Error>> {
+    // Run container in detached mode. It is named in advance, so that it can be
+    // removed even if `docker run` times out before printing its ID
+    let name = format!("servicemaker-zipper-{}", random_suffix());
+    let mut run_command = Command::new("docker");
+    run_command.args(["run", "-d", "--name", &name]);
+    if let Some(user) = user {
+        run_command.args(["--user", user]);
+    }
+    let mut run_child = run_command
+        .args([
+            "--entrypoint",
+            "bash",
+            image_name,
+            "-c",
+            &format!("/scripts/zipper.sh {} {}", project_dir, tar_output_path),
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .logged()
+        .spawn()?;
+    let Some(run_status) = wait_with_timeout(&mut run_child, timeout)? else {
+        // The container may have been created anyway; dropping it force-removes it
+        drop(Container {
+            id: name,
+            removed: false,
+        });
+        return Err(timeout_error("Starting the zipper container", timeout, image_name).into());
+    };
+    let container_output = run_child.wait_with_output()?;
+
+    if !run_status.success() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::TarGz,
+            format!(
+                "Failed to start Docker container: {}",
+                String::from_utf8_lossy(&container_output.stderr)
+            ),
+        )
+        .with_context(image_name)
+        .into());
+    }
+
+    let container = Container {
+        id: name,
+        removed: false,
+    };
+    debug!(
+        "Started container {}: {}",
+        container.id,
+        String::from_utf8_lossy(&container_output.stdout).trim()
+    );
+
+    // Wait for container to finish
+    debug!("Waiting for container to finish...");
+    let mut wait_child = Command::new("docker")
+        .args(["wait", &container.id])
+        .stdout(child_stdout())
+        .logged()
+        .spawn()?;
+    let Some(wait_status) = wait_with_timeout(&mut wait_child, timeout)? else {
+        return Err(timeout_error("The zipper container", timeout, &container.id).into());
+    };
+
+    if !wait_status.success() {
+        return Err(
+            ServiceMakerError::new(ErrorKind::TarGz, "Failed to wait for container")
+                .with_context(container.id.as_str())
+                .into(),
+        );
+    }
+
+    // Check exit code of the container
+    let exit_code_output = Command::new("docker")
+        .args(["inspect", "-f", "{{.State.ExitCode}}", &container.id])
+        .logged()
+        .output()?;
+
+    if !exit_code_output.status.success() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::TarGz,
+            "Failed to inspect container exit code",
+        )
+        .with_context(container.id.as_str())
+        .into());
+    }
+
+    let exit_code = String::from_utf8(exit_code_output.stdout)?
+        .trim()
+        .parse::<i32>()?;
+
+    Ok((container, exit_code))
+}
+
+/// The `uid:gid` of the invoking user, as printed by `id`
+fn current_user() -> Result<String, ServiceMakerError> {
+    let id = |flag: &str| -> Result<String, ServiceMakerError> {
+        let output = Command::new("id")
+            .arg(flag)
+            .logged()
+            .output()
+            .map_err(|e| {
+                ServiceMakerError::new(ErrorKind::Tooling, format!("Failed to run id: {}", e))
+            })?;
+        if !output.status.success() {
+            return Err(ServiceMakerError::new(
+                ErrorKind::Tooling,
+                format!("id {} failed", flag),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    Ok(format!("{}:{}", id("-u")?, id("-g")?))
+}
+
+/// Create project.tar.gz by running zipper.sh in a container of the built image,
+/// or on the host for `params.local`
+fn create_tar_gz(params: &TarGzParams) -> Result<(), Box<dyn std::error::Error>> {
+    let TarGzParams {
+        tar_file_path,
+        image_name,
+        project_dir,
+        tar_output_path,
+        run_as_current_user,
+        timeout,
+        ..
+    } = *params;
+    info!("\n=== Creating project.tar.gz ===");
+    if params.local {
+        return create_tar_gz_locally(params);
+    }
+
+    let user = if run_as_current_user {
+        Some(current_user()?)
+    } else {
+        None
+    };
+    let (mut container, mut exit_code) = run_zipper_container(
+        image_name,
+        project_dir,
+        tar_output_path,
+        user.as_deref(),
+        timeout,
+    )?;
+
+    // The image's user setup may not allow another user to read the project or
+    // write the archive, so fall back to the image's own user
+    if exit_code != 0
+        && let Some(ref user) = user
+    {
+        warn!(
+            "zipper container failed with code {} when running as user {}, \
+            retrying as the image's user",
+            exit_code, user
+        );
+        // Replacing the container force-removes the failed one
+        (container, exit_code) =
+            run_zipper_container(image_name, project_dir, tar_output_path, None, timeout)?;
+    }
+
+    if exit_code != 0 {
+        return Err(ServiceMakerError::new(
+            ErrorKind::TarGz,
+            format!("Container exited with code: {}", exit_code),
+        )
+        .with_context(container.id.as_str())
+        .into());
+    }
+
+    // Copy file from container to the host
+    if let Some(parent) = tar_file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    info!("Copying {} from container...", tar_output_path);
+    let mut copy_output = None;
+    for attempt in 1..=TAR_GZ_COPY_ATTEMPTS {
+        let output = Command::new("docker")
+            .args([
                 "cp",
-                &format!("{}:/tmp/project.tar.gz", container_id),
+                &format!("{}:{}", container.id, tar_output_path),
                 tar_file_path.to_str().unwrap(),
             ])
-            .status()?;
+            .logged()
+            .output()?;
+        if output.status.success() {
+            copy_output = None;
+            break;
+        }
+        warn!(
+            "docker cp failed (attempt {}/{}): {}",
+            attempt,
+            TAR_GZ_COPY_ATTEMPTS,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        copy_output = Some(output);
+        if attempt < TAR_GZ_COPY_ATTEMPTS {
+            std::thread::sleep(std::time::Duration::from_secs(attempt as u64));
+        }
+    }
+
+    if let Some(output) = copy_output {
+        let output_dir = Path::new(tar_output_path)
+            .parent()
+            .and_then(Path::to_str)
+            .unwrap_or("/");
+        return Err(ServiceMakerError::new(
+            ErrorKind::TarGz,
+            format!(
+                "Failed to copy {} from container after {} attempts: {}\n\
+                Files added to {} in the container:\n{}",
+                tar_output_path,
+                TAR_GZ_COPY_ATTEMPTS,
+                String::from_utf8_lossy(&output.stderr).trim(),
+                output_dir,
+                container_dir_listing(&container.id, output_dir)
+            ),
+        )
+        .with_context(container.id.as_str())
+        .into());
+    }
+
+    container.remove()?;
+
+    if tar_file_path.exists() {
+        info!(
+            "✓ project.tar.gz created successfully: {}",
+            tar_file_path.display()
+        );
+    } else {
+        return Err(ServiceMakerError::new(
+            ErrorKind::TarGz,
+            format!("project.tar.gz not found at: {}", tar_file_path.display()),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Create project.tar.gz like zipper.sh, but from the project copy in the temporary
+/// directory: an empty `the_venv/`, the `entrypoint` file and the project directory
+fn create_tar_gz_locally(params: &TarGzParams) -> Result<(), Box<dyn std::error::Error>> {
+    let tar_error = |e: io::Error| {
+        ServiceMakerError::new(ErrorKind::TarGz, e.to_string())
+            .with_context(params.tar_file_path.display().to_string())
+    };
+    info!(
+        "Archiving on the host: the_venv entrypoint {}",
+        params.project_dir
+    );
+    if let Some(parent) = params.tar_file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = fs::File::create(params.tar_file_path).map_err(tar_error)?;
+    let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(
+        file,
+        flate2::Compression::default(),
+    ));
+    archive.follow_symlinks(false);
+
+    let mut venv = tar::Header::new_gnu();
+    venv.set_entry_type(tar::EntryType::Directory);
+    venv.set_mode(0o755);
+    venv.set_size(0);
+    venv.set_mtime(now_unix());
+    archive
+        .append_data(&mut venv, "the_venv/", io::empty())
+        .map_err(tar_error)?;
+
+    let entrypoint = format!("/project/{}/{}\n", params.project_dir, params.entrypoint);
+    let mut header = tar::Header::new_gnu();
+    header.set_mode(0o644);
+    header.set_size(entrypoint.len() as u64);
+    header.set_mtime(now_unix());
+    archive
+        .append_data(&mut header, "entrypoint", entrypoint.as_bytes())
+        .map_err(tar_error)?;
+
+    archive
+        .append_dir_all(params.project_dir, params.temp_dir.join(params.project_dir))
+        .map_err(tar_error)?;
+    archive
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(tar_error)?;
+
+    info!(
+        "✓ project.tar.gz created successfully: {}",
+        params.tar_file_path.display()
+    );
+    Ok(())
+}
+
+fn now_unix() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Whether pyproject.toml declares dependencies (`project.dependencies`, or Poetry's
+/// `tool.poetry.dependencies` besides Python), which the image build installs into the
+/// virtual environment
+fn pyproject_has_dependencies(project_home: &Path) -> bool {
+    // The dependencies of setup.py cannot be known without running it
+    if !project_home.join("pyproject.toml").exists() && has_setuptools_manifest(project_home) {
+        return true;
+    }
+    fs::read_to_string(project_home.join("pyproject.toml"))
+        .ok()
+        .and_then(|content| toml::from_str::<Value>(&content).ok())
+        .and_then(|value| {
+            let (project, section) = pyproject_metadata_table(&value).ok()?;
+            let dependencies = project.get("dependencies")?;
+            if section == "tool.poetry" {
+                // Poetry lists the Python version among the dependencies
+                let dependencies = dependencies.as_table()?;
+                Some(dependencies.keys().any(|name| name != "python"))
+            } else {
+                Some(!dependencies.as_array()?.is_empty())
+            }
+        })
+        .unwrap_or(false)
+}
+
+/// Write the Helm chart into `chart_dir`, lint it and package it into `out_dir`,
+/// followed by one chart per variant. Returns the paths of the packaged charts.
+/// Runs on its own thread, hence the (`Send`) `ServiceMakerError` instead of a boxed error.
+fn generate_helm_chart(
+    temp_dir: &Path,
+    out_dir: &Path,
+    chart_dir: &Path,
+    params: &ChartParams,
+    manifests_path: Option<&Path>,
+    render_namespace: Option<&str>,
+    strict_lint: bool,
+) -> Result<Vec<PathBuf>, ServiceMakerError> {
+    info!("\n=== Generating Helm Chart ===");
+    write_chart(chart_dir, params)?;
+    lint_helm_chart(chart_dir, strict_lint)?;
+
+    if let Some(manifests_path) = manifests_path {
+        render_helm_chart(chart_dir, manifests_path, render_namespace)?;
+    }
+
+    let mut chart_file_paths = vec![package_helm_chart(temp_dir, out_dir, chart_dir, params)?];
+
+    for variant in params.variants {
+        info!("\n=== Generating Helm Chart variant {} ===", variant.name);
+        let variant_name = format!("{}-{}", params.service_name, variant.name);
+        let variant_params = ChartParams {
+            service_name: &variant_name,
+            ..*params
+        };
+        let variant_dir = temp_dir.join(&variant_name);
+        write_chart(&variant_dir, &variant_params)?;
+
+        // Reading and writing values.yaml only fails unexpectedly, while a failed merge
+        // comes from the variant's values file
+        let values_path = variant_dir.join("values.yaml");
+        let io_error = |e: io::Error| {
+            ServiceMakerError::new(
+                ErrorKind::Internal,
+                format!("Failed to access values.yaml: {}", e),
+            )
+            .with_context(values_path.display().to_string())
+        };
+        let content = fs::read_to_string(&values_path).map_err(io_error)?;
+        let merged_values = serde_norway::from_str::<serde_norway::Value>(&content)
+            .and_then(|mut values| {
+                merge_yaml(&mut values, variant.values.clone());
+                serde_norway::to_string(&values)
+            })
+            .map(|yaml| {
+                format!(
+                    "# Generated values merged with the overlay of variant {}\n{}",
+                    variant.name, yaml
+                )
+            })
+            .map_err(|e| {
+                ServiceMakerError::new(
+                    ErrorKind::Config,
+                    format!(
+                        "Failed to apply the values of variant {}: {}",
+                        variant.name, e
+                    ),
+                )
+                .with_context(values_path.display().to_string())
+            })?;
+        fs::write(&values_path, merged_values).map_err(io_error)?;
+
+        lint_helm_chart(&variant_dir, strict_lint)?;
+        chart_file_paths.push(package_helm_chart(
+            temp_dir,
+            out_dir,
+            &variant_dir,
+            &variant_params,
+        )?);
+    }
+
+    Ok(chart_file_paths)
+}
+
+/// Write the chart files for `params` into `chart_dir`
+fn write_chart(chart_dir: &Path, params: &ChartParams) -> Result<(), ServiceMakerError> {
+    debug!("Generating charts template in {}", chart_dir.display());
+    // A failure to write the files is unexpected (e.g. a full disk), not a lint finding
+    copy_and_replace_charts(chart_dir, params).map_err(|e| {
+        ServiceMakerError::new(
+            ErrorKind::Internal,
+            format!("Failed to generate Helm chart: {}", e),
+        )
+        .with_context(chart_dir.display().to_string())
+    })
+}
+
+/// Lint the chart with `helm lint`. Its findings are summarized: errors fail the run,
+/// warnings only with `strict_lint` (--strict-lint)
+fn lint_helm_chart(chart_dir: &Path, strict_lint: bool) -> Result<(), ServiceMakerError> {
+    info!("\nRunning helm lint...");
+    let output = Command::new("helm")
+        .args(["lint", chart_dir.to_str().unwrap()])
+        .logged()
+        .output()
+        .map_err(|e| {
+            ServiceMakerError::new(
+                ErrorKind::Tooling,
+                format!("Failed to run helm lint: {}", e),
+            )
+        })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+    for line in stdout.lines().chain(stderr.lines()) {
+        let line = line.trim();
+        if let Some(warning) = line.strip_prefix("[WARNING]") {
+            warnings.push(warning.trim());
+        } else if let Some(error) = line.strip_prefix("[ERROR]") {
+            errors.push(error.trim());
+        } else if !line.is_empty() {
+            debug!("helm lint: {}", line);
+        }
+    }
+    for warning in &warnings {
+        warn!("helm lint: {}", warning);
+    }
+    info!(
+        "helm lint: {} error(s), {} warning(s)",
+        errors.len(),
+        warnings.len()
+    );
+
+    if !output.status.success() || !errors.is_empty() {
+        let message = if errors.is_empty() {
+            format!("Helm lint failed: {}", stderr.trim())
+        } else {
+            format!("Helm lint failed: {}", errors.join("; "))
+        };
+        return Err(ServiceMakerError::new(ErrorKind::HelmLint, message)
+            .with_context(chart_dir.display().to_string()));
+    }
+    if strict_lint && !warnings.is_empty() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::HelmLint,
+            format!(
+                "Helm lint reported {} warning(s) (--strict-lint)",
+                warnings.len()
+            ),
+        )
+        .with_context(chart_dir.display().to_string()));
+    }
+
+    info!("✓ Helm lint passed");
+    Ok(())
+}
+
+/// Package the chart in `chart_dir` into `out_dir` and return the path of the .tgz
+fn package_helm_chart(
+    temp_dir: &Path,
+    out_dir: &Path,
+    chart_dir: &Path,
+    params: &ChartParams,
+) -> Result<PathBuf, ServiceMakerError> {
+    // Run helm package
+    info!("\nRunning helm package...");
+    let package_status = Command::new("helm")
+        .args(["package", chart_dir.to_str().unwrap(), "--destination"])
+        .arg(out_dir)
+        .current_dir(temp_dir)
+        .stdout(child_stdout())
+        .logged()
+        .status()
+        .map_err(|e| {
+            ServiceMakerError::new(
+                ErrorKind::Tooling,
+                format!("Failed to run helm package: {}", e),
+            )
+        })?;
+
+    if !package_status.success() {
+        return Err(
+            ServiceMakerError::new(ErrorKind::HelmPackage, "Helm package failed")
+                .with_context(chart_dir.display().to_string()),
+        );
+    }
+
+    // Find the generated chart file
+    let chart_file_name = format!("{}-{}.tgz", params.service_name, params.chart_version);
+    let chart_file_path = out_dir.join(&chart_file_name);
+
+    if chart_file_path.exists() {
+        info!(
+            "✓ Helm chart packaged successfully: {}",
+            chart_file_path.display()
+        );
+        Ok(chart_file_path)
+    } else {
+        Err(ServiceMakerError::new(
+            ErrorKind::HelmPackage,
+            format!("Helm chart file not found: {}", chart_file_path.display()),
+        ))
+    }
+}
+
+/// Merge `overlay` into `base`: mappings are merged key by key, anything else is replaced
+fn merge_yaml(base: &mut serde_norway::Value, overlay: serde_norway::Value) {
+    match (base, overlay) {
+        (serde_norway::Value::Mapping(base), serde_norway::Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Parse a --chart-variant NAME:VALUES_FILE entry and read its values file
+fn read_chart_variant(entry: &str) -> Result<ChartVariant, ServiceMakerError> {
+    let Some((name, values_file)) = entry.split_once(':') else {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!(
+                "Invalid --chart-variant '{}': expected NAME:VALUES_FILE",
+                entry
+            ),
+        ));
+    };
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!(
+                "Invalid --chart-variant name '{}': use lowercase letters, digits and '-'",
+                name
+            ),
+        ));
+    }
+
+    let content = fs::read_to_string(values_file).map_err(|e| {
+        ServiceMakerError::new(
+            ErrorKind::Config,
+            format!("Failed to read values file of variant {}: {}", name, e),
+        )
+        .with_context(values_file)
+    })?;
+    let mut values: serde_norway::Value = serde_norway::from_str(&content).map_err(|e| {
+        ServiceMakerError::new(
+            ErrorKind::Config,
+            format!("Invalid YAML in values file of variant {}: {}", name, e),
+        )
+        .with_context(values_file)
+    })?;
+    // An empty file changes nothing
+    if values.is_null() {
+        values = serde_norway::Value::Mapping(serde_norway::Mapping::new());
+    }
+    if !values.is_mapping() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!("Values file of variant {} must contain a mapping", name),
+        )
+        .with_context(values_file));
+    }
+
+    Ok(ChartVariant {
+        name: name.to_string(),
+        values,
+    })
+}
+
+fn preflight_check(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    if !args.chart_only {
+        check_tool(
+            "docker",
+            &["--version"],
+            "Docker is required to build images. Please install Docker and make sure it is on the PATH.",
+        )?;
+    }
+
+    if args.platform.is_some() {
+        check_tool(
+            "docker",
+            &["buildx", "version"],
+            "--platform requires the Docker buildx plugin, which was not found. \
+            See https://docs.docker.com/go/buildx/ for installation instructions.",
+        )?;
+    }
+
+    if !args.no_helm {
+        check_tool(
+            "helm",
+            &["version"],
+            "Helm is required to generate the Helm chart. Please install Helm and make sure it is on the PATH.",
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Whether `image_name` exists in the local Docker image store. A failing `docker image
+/// inspect` means a missing image only if it says so; anything else (e.g. the daemon is not
+/// running) is an error rather than a false "does not exist".
+fn local_image_exists(image_name: &str) -> Result<bool, ServiceMakerError> {
+    let output = Command::new("docker")
+        .args(["image", "inspect", "--format", "{{.Id}}", image_name])
+        .logged()
+        .output()
+        .map_err(|e| {
+            ServiceMakerError::new(ErrorKind::Tooling, format!("Failed to run docker: {}", e))
+        })?;
+    if output.status.success() {
+        return Ok(true);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.to_lowercase().contains("no such image") {
+        Ok(false)
+    } else {
+        Err(ServiceMakerError::new(
+            ErrorKind::Tooling,
+            format!(
+                "Could not check whether image {} exists, is the Docker daemon reachable? {}",
+                image_name,
+                stderr.trim()
+            ),
+        ))
+    }
+}
+
+/// Registry host of an image reference; references without one are from Docker Hub
+fn image_registry(image: &str) -> &str {
+    match image.split_once('/') {
+        Some((host, _)) if host.contains(['.', ':']) || host == "localhost" => host,
+        _ => "docker.io",
+    }
+}
+
+/// Run `program args...` quietly and fail with `hint` if it cannot be run successfully
+fn check_tool(program: &str, args: &[&str], hint: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let available = Command::new(program)
+        .args(args)
+        .logged()
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+
+    if available {
+        Ok(())
+    } else {
+        Err(ServiceMakerError::new(ErrorKind::Tooling, hint).into())
+    }
+}
+
+/// Resolve an image tag to its current registry digest (`repo@sha256:...`)
+/// The image is pulled first so that the digest reflects the registry, not a stale local copy.
+fn resolve_base_image_digest(base_image: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if base_image.contains("@sha256:") {
+        return Ok(base_image.to_string());
+    }
+
+    info!("Pulling base image {} to resolve its digest...", base_image);
+    let pull_status = Command::new("docker")
+        .args(["pull", base_image])
+        .stdout(child_stdout())
+        .logged()
+        .status()?;
+    if !pull_status.success() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::DockerBuild,
+            format!("Failed to pull base image {}", base_image),
+        )
+        .into());
+    }
+
+    let output = Command::new("docker")
+        .args([
+            "inspect",
+            "--format",
+            "{{index .RepoDigests 0}}",
+            base_image,
+        ])
+        .logged()
+        .output()?;
+    let digest = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || !digest.contains("@sha256:") {
+        return Err(ServiceMakerError::new(
+            ErrorKind::DockerBuild,
+            format!(
+                "Could not resolve digest of base image {}: {}",
+                base_image,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        )
+        .into());
+    }
+
+    Ok(digest)
+}
+
+fn prompt(message: &str) -> Result<String, io::Error> {
+    status_inline!("{}: ", message);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// Prompt with a default value which is used when the answer is empty
+fn prompt_with_default(message: &str, default: Option<&str>) -> Result<String, io::Error> {
+    match default {
+        Some(default) if !default.is_empty() => {
+            let answer = prompt(&format!("{} [{}]", message, default))?;
+            if answer.is_empty() {
+                Ok(default.to_string())
+            } else {
+                Ok(answer)
+            }
+        }
+        _ => prompt(message),
+    }
+}
+
+/// Ask a yes/no question, returning `default` when the answer is empty
+fn prompt_yes_no(message: &str, default: bool) -> Result<bool, io::Error> {
+    let hint = if default { "Y/n" } else { "y/N" };
+    loop {
+        let answer = prompt(&format!("{} [{}]", message, hint))?.to_lowercase();
+        match answer.as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => status!("Please answer 'y' or 'n'"),
+        }
+    }
+}
+
+/// Ask for confirmation of an action, defaulting to no. With `assume_yes` (--yes) the
+/// answer is yes without asking; without a terminal to ask on, that is required.
+fn confirm(message: &str, assume_yes: bool) -> Result<bool, ServiceMakerError> {
+    if assume_yes {
+        info!("{} yes (--yes)", message);
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!(
+                "{} Cannot ask without a terminal, add --yes to confirm",
+                message
+            ),
+        ));
+    }
+    prompt_yes_no(message, false)
+        .map_err(|e| ServiceMakerError::new(ErrorKind::Internal, e.to_string()))
+}
+
+/// Read a configuration file as written by `--interactive-config`
+fn load_config(config_path: &Path) -> Result<ServiceConfig, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(config_path).map_err(|e| {
+        format!(
+            "Failed to read configuration file {}: {}",
+            config_path.display(),
+            e
+        )
+    })?;
+    let config: ServiceConfig = toml::from_str(&content).map_err(|e| {
+        format!(
+            "Failed to parse configuration file {}: {}",
+            config_path.display(),
+            e
+        )
+    })?;
+    Ok(config)
+}
+
+/// Use configuration file values for everything not given on the command line
+fn apply_config(args: &mut Args, config: ServiceConfig) {
+    if args.name.is_none() {
+        args.name = config.name;
+    }
+    if args.project_home.is_none() {
+        args.project_home = config.project_home;
+    }
+    if args.base_image.is_none() {
+        args.base_image = config.base_image;
+    }
+    if args.port.is_none() {
+        args.port = config.port;
+    }
+    if args.image_name.is_none() {
+        args.image_name = config.image_name;
+    }
+    if args.entrypoint.is_none() {
+        args.entrypoint = config.entrypoint;
+    }
+    if args.detector_script.is_none() {
+        args.detector_script = config.detector_script;
+    }
+    if args.dockerfile.is_none() {
+        args.dockerfile = config.dockerfile;
+    }
+    if args.allowed_registries.is_empty() {
+        args.allowed_registries = config.allowed_registries.unwrap_or_default();
+    }
+    args.push = args.push || config.push.unwrap_or(false);
+    args.make_tar_gz = args.make_tar_gz || config.make_tar_gz.unwrap_or(false);
+
+    // Chart options; those with a default are only replaced when not given explicitly
+    if !args.is_given("replicas")
+        && let Some(replicas) = config.replicas
+    {
+        args.replicas = replicas;
+    }
+    if !args.is_given("service_type")
+        && let Some(service_type) = config.service_type
+    {
+        args.service_type = service_type;
+    }
+    if !args.is_given("chart_type")
+        && let Some(chart_type) = config.chart_type
+    {
+        args.chart_type = chart_type;
+    }
+    if args.ingress_host.is_none() {
+        args.ingress_host = config.ingress_host;
+    }
+    if args.cpu_request.is_none() {
+        args.cpu_request = config.cpu_request;
+    }
+    if args.cpu_limit.is_none() {
+        args.cpu_limit = config.cpu_limit;
+    }
+    if args.memory_request.is_none() {
+        args.memory_request = config.memory_request;
+    }
+    if args.memory_limit.is_none() {
+        args.memory_limit = config.memory_limit;
+    }
+    if args.chart_version.is_none() {
+        args.chart_version = config.chart_version;
+    }
+}
+
+/// Interactive wizard: asks for every setting (offering detected values as
+/// defaults) and writes them to a configuration file without building anything
+fn run_config_wizard(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    status!("=== ServiceMaker Configuration Wizard ===");
+    status!("Press Enter to accept the value shown in brackets.\n");
+
+    let project_home = match &args.project_home {
+        Some(project_home) => project_home.clone(),
+        None => PathBuf::from(prompt("Project home path")?),
+    };
+    if !project_home.exists() {
+        return Err(format!("Project home does not exist: {}", project_home.display()).into());
+    }
+
+    let detected_type =
+        detect_project_type_with_script(&project_home, args.detector_script.as_deref())?;
+    status!("Detected project type: {}", detected_type);
+    let project_type = loop {
+        let answer = prompt_with_default(
+            &format!("Project type ({})", PROJECT_TYPES.join(", ")),
+            Some(&detected_type),
+        )?;
+        if PROJECT_TYPES.contains(&answer.as_str()) {
+            break answer;
+        }
+        status!("Unsupported project type '{}'", answer);
+    };
+
+    // Custom projects need a Dockerfile template
+    let dockerfile = if project_type == "custom" {
+        let default_dockerfile = args.dockerfile.as_ref().map(|d| d.display().to_string());
+        Some(PathBuf::from(prompt_with_default(
+            "Dockerfile template",
+            default_dockerfile.as_deref(),
+        )?))
+    } else {
+        args.dockerfile.clone()
+    };
+
+    // Derive defaults the same way a regular run would
+    let (detected_name, default_base_image, detected_entrypoint) = match project_type.as_str() {
+        "python" => (
+            read_name_from_pyproject(&project_home).ok(),
+            DEFAULT_PYTHON_BASE_IMAGE,
+            find_single_py_file(&project_home).ok().flatten(),
+        ),
+        "nodejs" => (
+            read_name_from_package_json(&project_home).ok(),
+            if args.runtime.as_deref() == Some("bun")
+                || args.runtime.is_none() && has_bun_lockfile(&project_home)
+            {
+                DEFAULT_BUN_BASE_IMAGE
+            } else {
+                DEFAULT_NODEJS_BASE_IMAGE
+            },
+            Some(
+                detect_nodejs_entrypoint(&project_home)
+                    .ok()
+                    .flatten()
+                    .unwrap_or_else(|| "index.js".to_string()),
+            ),
+        ),
+        "rust" => (
+            read_service_info_from_cargo_toml(&project_home)
+                .ok()
+                .map(|info| info.name),
+            DEFAULT_RUST_BASE_IMAGE,
+            detect_rust_binary(&project_home).ok().flatten(),
+        ),
+        "deno" => (
+            read_service_info_from_deno_json(&project_home)
+                .ok()
+                .map(|info| info.name),
+            DEFAULT_DENO_BASE_IMAGE,
+            match detect_deno_entrypoint(&project_home) {
+                Ok(DenoStart::Module(module)) => Some(module),
+                _ => None,
+            },
+        ),
+        "custom" => (None, "", None),
+        _ => return Err(format!("Unsupported project type: {}", project_type).into()),
+    };
+
+    let name = prompt_with_default(
+        "Project name",
+        args.name.as_deref().or(detected_name.as_deref()),
+    )?;
+    let base_image = prompt_with_default(
+        "Base Docker image",
+        args.base_image.as_deref().or(Some(default_base_image)),
+    )?;
+    let entrypoint = prompt_with_default(
+        "Entrypoint script (relative to project home)",
+        args.entrypoint
+            .as_deref()
+            .or(detected_entrypoint.as_deref()),
+    )?;
+    let port = loop {
+        let default_port = args.port.map(|p| p.to_string());
+        let port_str = prompt_with_default("Exposed port number", default_port.as_deref())?;
+        match parse_port(&port_str) {
+            Ok(port) => break port,
+            Err(e) => status!("{}", e),
+        }
+    };
+    let image_name = prompt_with_default(
+        "Docker image name",
+        Some(args.image_name.as_deref().unwrap_or(&name)),
+    )?;
+    let push = prompt_yes_no("Push the image after building?", args.push)?;
+    let make_tar_gz = prompt_yes_no("Create project.tar.gz?", args.make_tar_gz)?;
+
+    // Helm chart options; optional ones are left out when the answer is empty
+    let optional = |message: &str, default: &Option<String>| {
+        prompt_with_default(message, default.as_deref())
+            .map(|answer| Some(answer).filter(|a| !a.is_empty()))
+    };
+    let chart_type = loop {
+        let answer =
+            prompt_with_default("Chart type (application, library)", Some(&args.chart_type))?;
+        if answer == "application" || answer == "library" {
+            break answer;
+        }
+        status!("Please answer 'application' or 'library'");
+    };
+    let chart_version = optional(
+        "Chart version (empty: the project version)",
+        &args.chart_version,
+    )?;
+    let replicas = loop {
+        let answer = prompt_with_default("Replicas", Some(&args.replicas.to_string()))?;
+        match answer.parse::<u32>() {
+            Ok(replicas) if replicas > 0 => break replicas,
+            _ => status!(
+                "Invalid number of replicas '{}': expected at least 1",
+                answer
+            ),
+        }
+    };
+    let service_type = loop {
+        let answer = prompt_with_default(
+            "Service type (ClusterIP, NodePort, LoadBalancer)",
+            Some(&args.service_type),
+        )?;
+        if ["ClusterIP", "NodePort", "LoadBalancer"].contains(&answer.as_str()) {
+            break answer;
+        }
+        status!("Unsupported service type '{}'", answer);
+    };
+    let ingress_host = optional("Ingress host (empty: no Ingress)", &args.ingress_host)?;
+    let (cpu_request, cpu_limit, memory_request, memory_limit) =
+        if prompt_yes_no("Set CPU and memory requests and limits?", false)? {
+            (
+                optional("CPU request, e.g. 100m", &args.cpu_request)?,
+                optional("CPU limit, e.g. 1", &args.cpu_limit)?,
+                optional("Memory request, e.g. 128Mi", &args.memory_request)?,
+                optional("Memory limit, e.g. 512Mi", &args.memory_limit)?,
+            )
+        } else {
+            (
+                args.cpu_request.clone(),
+                args.cpu_limit.clone(),
+                args.memory_request.clone(),
+                args.memory_limit.clone(),
+            )
+        };
+
+    let config = ServiceConfig {
+        name: Some(name),
+        project_home: Some(project_home),
+        project_type: Some(project_type),
+        base_image: Some(base_image),
+        port: Some(port),
+        image_name: Some(image_name),
+        entrypoint: Some(entrypoint).filter(|e| !e.is_empty()),
+        push: Some(push),
+        make_tar_gz: Some(make_tar_gz),
+        detector_script: args.detector_script.clone(),
+        dockerfile,
+        allowed_registries: Some(args.allowed_registries.clone()).filter(|r| !r.is_empty()),
+        replicas: Some(replicas),
+        service_type: Some(service_type),
+        ingress_host,
+        cpu_request,
+        cpu_limit,
+        memory_request,
+        memory_limit,
+        chart_type: Some(chart_type),
+        chart_version,
+    };
+
+    let config_path = args
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_FILE));
+    fs::write(&config_path, toml::to_string_pretty(&config)?)?;
+
+    status!("\n✓ Configuration written to {}", config_path.display());
+    status!(
+        "Build with: servicemaker --config {}",
+        config_path.display()
+    );
+    Ok(())
+}
+
+/// Select a base image matching the runtime version declared in an asdf
+/// `.tool-versions` file (e.g. `python 3.12.4` -> `arangodb/py12base:latest`,
+/// `nodejs 22.3.0` -> `arangodb/node22base:latest`). Returns None (with a
+/// warning if a version was declared) when there is no matching known image.
+fn base_image_from_tool_versions(project_home: &Path, project_type: &str) -> Option<String> {
+    let content = fs::read_to_string(project_home.join(".tool-versions")).ok()?;
+    let tool = match project_type {
+        "python" => "python",
+        "nodejs" => "nodejs",
+        _ => return None,
+    };
+
+    let version = content.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut parts = line.split_whitespace();
+        if parts.next() == Some(tool) {
+            parts.next().map(|v| v.to_string())
+        } else {
+            None
+        }
+    })?;
+
+    let mut numbers = version.split('.');
+    let major = numbers.next().unwrap_or("");
+    let image = match tool {
+        "python" => {
+            let minor = numbers.next().unwrap_or("");
+            format!("arangodb/py{}{}base:latest", major, minor)
+        }
+        _ => format!("arangodb/node{}base:latest", major),
+    };
+
+    if KNOWN_BASE_IMAGES.contains(&image.as_str()) {
+        info!(
+            "Selected base image {} for {} {} from .tool-versions",
+            image, tool, version
+        );
+        Some(image)
+    } else {
+        warn!(
+            "no known base image for {} {} from .tool-versions, using the default \
+            (use --base-image to choose one explicitly)",
+            tool, version
+        );
+        None
+    }
+}
+
+/// The Node.js major version a project declares in `.nvmrc` or in `engines.node` of
+/// package.json, together with where it was found. Ranges such as `>=18` or `18 || 20`
+/// do not name a single major version and are ignored.
+fn declared_node_version(project_home: &Path) -> Option<(String, u32)> {
+    if let Ok(content) = fs::read_to_string(project_home.join(".nvmrc"))
+        && let Some(major) = content.lines().next().and_then(node_major)
+    {
+        return Some((".nvmrc".to_string(), major));
+    }
+
+    let content = fs::read_to_string(project_home.join("package.json")).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let engines_node = value.get("engines")?.get("node")?.as_str()?;
+    node_major(engines_node).map(|major| ("package.json engines.node".to_string(), major))
+}
+
+/// Extract the major version from a Node.js version such as "18", "v18.17.0", "^18.0.0" or "18.x"
+fn node_major(version: &str) -> Option<u32> {
+    let version = version.trim();
+    let version = version
+        .strip_prefix(['^', '~', '='])
+        .unwrap_or(version)
+        .trim_start_matches('v');
+    if version.contains([' ', '<', '>', '|']) {
+        return None;
+    }
+    version.split('.').next()?.parse().ok()
+}
+
+/// Extract the Node.js major version from a base image name:
+/// `arangodb/node22base:latest` -> 22 and `node:18-slim` -> 18
+fn extract_node_major(base_image: &str) -> Option<u32> {
+    let without_digest = base_image.split('@').next().unwrap_or(base_image);
+    let repository = image_repository(without_digest);
+    let name = repository.rsplit('/').next().unwrap_or(repository);
+
+    if let Some(major) = name
+        .strip_prefix("node")
+        .and_then(|rest| rest.strip_suffix("base"))
+    {
+        return major.parse().ok();
+    }
+
+    if name == "node" {
+        let tag = without_digest.get(repository.len() + 1..)?;
+        return node_major(tag.split('-').next()?);
+    }
+
+    None
+}
+
+/// Select the arangodb/nodeNNbase image for a declared Node.js major version
+fn base_image_for_node_major(major: u32, source: &str) -> Option<String> {
+    let image = format!("arangodb/node{}base:latest", major);
+    if KNOWN_BASE_IMAGES.contains(&image.as_str()) {
+        info!(
+            "Selected base image {} for Node.js {} from {}",
+            image, major, source
+        );
+        Some(image)
+    } else {
+        warn!(
+            "no known base image for Node.js {} from {}, using the default \
+            (use --base-image to choose one explicitly)",
+            major, source
+        );
+        None
+    }
+}
+
+/// The Python version for the Dockerfile: --python-version if given, else detected
+fn resolve_python_version(
+    python_version: Option<&str>,
+    project_home: &Path,
+    base_image: &str,
+) -> String {
+    match python_version {
+        Some(python_version) => python_version.trim().to_string(),
+        None => detect_python_version(project_home, base_image),
+    }
+}
+
+/// Determine the Python version for the Dockerfile: from `.python-version`, then from
+/// `project.requires-python` in pyproject.toml, then from the base image name.
+/// Warns when the project asks for a different version than the base image provides.
+fn detect_python_version(project_home: &Path, base_image: &str) -> String {
+    let image_version = extract_python_version(base_image);
+
+    if let Ok(content) = fs::read_to_string(project_home.join(".python-version"))
+        && let Some(version) = content.lines().next().and_then(python_major_minor)
+    {
+        if let Some(ref image_version) = image_version
+            && version != *image_version
+        {
+            warn!(
+                ".python-version requests Python {}, but base image {} provides Python {}",
+                version, base_image, image_version
+            );
+        }
+        return version;
+    }
+
+    let requires_python = fs::read_to_string(project_home.join("pyproject.toml"))
+        .ok()
+        .and_then(|content| toml::from_str::<Value>(&content).ok())
+        .and_then(|value| {
+            value
+                .get("project")?
+                .get("requires-python")?
+                .as_str()
+                .map(|r| r.to_string())
+        });
+    if let Some(requires_python) = requires_python {
+        if let Some(image_version) = image_version.as_ref()
+            && python_version_matches(image_version, &requires_python)
+        {
+            return image_version.clone();
+        }
+        // Use the lowest version the project accepts
+        let lowest = requires_python.split(',').map(str::trim).find_map(|spec| {
+            ["==", "~=", ">="]
+                .iter()
+                .find_map(|op| spec.strip_prefix(op))
+                .and_then(python_major_minor)
+        });
+        if let Some(version) = lowest {
+            if let Some(image_version) = image_version {
+                warn!(
+                    "pyproject.toml requires Python {}, but base image {} provides Python {}",
+                    requires_python, base_image, image_version
+                );
+            }
+            return version;
+        }
+    }
+
+    image_version.unwrap_or_else(|| {
+        warn!(
+            "could not determine the Python version of base image {}, assuming {} \
+            (use --python-version to set it)",
+            base_image, FALLBACK_PYTHON_VERSION
+        );
+        FALLBACK_PYTHON_VERSION.to_string()
+    })
+}
+
+/// Extract "3.12" from a version string such as "3.12.4", "3.12" or "3.12.*"
+fn python_major_minor(version: &str) -> Option<String> {
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.trim();
+    let minor = parts.next()?.trim();
+    if major.parse::<u32>().is_ok() && minor.parse::<u32>().is_ok() {
+        Some(format!("{}.{}", major, minor))
+    } else {
+        None
+    }
+}
+
+/// Check a major.minor Python version against a requires-python specifier such as
+/// ">=3.10,<3.13" (patch versions and wildcards are ignored)
+fn python_version_matches(version: &str, requires_python: &str) -> bool {
+    let parse = |v: &str| -> Option<(u32, u32)> {
+        let major_minor = python_major_minor(v)?;
+        let (major, minor) = major_minor.split_once('.')?;
+        Some((major.parse().ok()?, minor.parse().ok()?))
+    };
+    let Some(version) = parse(version) else {
+        return false;
+    };
+
+    requires_python.split(',').map(str::trim).all(|spec| {
+        let (op, bound) = ["==", "~=", ">=", "<=", "!=", ">", "<"]
+            .iter()
+            .find_map(|op| spec.strip_prefix(op).map(|rest| (*op, rest)))
+            .unwrap_or(("==", spec));
+        // "~=3.11.0" allows 3.11.* only, while "~=3.11" allows any 3.x from 3.11 on
+        let compatible_minor = op == "~=" && bound.split('.').count() > 2;
+        // Specifiers with only a major version (e.g. ">=3") are not restrictive at this level
+        let Some(bound) = parse(bound) else {
+            return true;
+        };
+        match op {
+            "==" => version == bound,
+            "!=" => version != bound,
+            "~=" if compatible_minor => version == bound,
+            "~=" | ">=" => version >= bound,
+            "<=" => version <= bound,
+            ">" => version > bound,
+            _ => version < bound,
+        }
+    })
+}
+
+/// Extract the Python version from a base image name:
+/// `arangodb/py12base:latest` -> "3.12" and `python:3.11-slim` -> "3.11"
+fn extract_python_version(base_image: &str) -> Option<String> {
+    let without_digest = base_image.split('@').next().unwrap_or(base_image);
+    let repository = image_repository(without_digest);
+    let name = repository.rsplit('/').next().unwrap_or(repository);
+
+    // ArangoDB base images: pyNNbase, where NN is the minor version of Python 3
+    if let Some(minor) = name
+        .strip_prefix("py")
+        .and_then(|rest| rest.strip_suffix("base"))
+        .filter(|minor| minor.len() == 2 && minor.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Some(format!("3.{}", minor));
+    }
+
+    // Official images: python:3.X, python:3.X.Y, python:3.X-slim, ...
+    if name == "python" {
+        let tag = without_digest.get(repository.len() + 1..)?;
+        return python_major_minor(tag.split('-').next()?);
+    }
+
+    None
+}
+
+/// Render the unified Dockerfile template for a Python project
+fn modify_dockerfile_python(
+    template: &str,
+    params: &DockerfileParams,
+    python_version: &str,
+) -> Result<String, minijinja::Error> {
+    render_dockerfile(
+        template,
+        context! {
+            PROJECT_TYPE => "python",
+            WORKDIR => params.project_dir,
+            PYTHON_VERSION => python_version,
+            ..dockerfile_context(params)
+        },
+    )
+}
+
+/// Render the unified Dockerfile template for a Node.js project
+/// Sets up NODE_PATH to resolve from project node_modules first, then base node_modules.
+/// `package_manager_install` replaces the install of the packages missing in the base image,
+/// `build_command` compiles a TypeScript project before that.
+fn modify_dockerfile_nodejs(
+    template: &str,
+    params: &DockerfileParams,
+    package_manager_install: Option<&str>,
+    build_command: Option<String>,
+) -> Result<String, minijinja::Error> {
+    // Node.js app structure:
+    // - COPY copies the project directory directly
+    // - WORKDIR is /project/{project-dir}
+    // - node_modules is in /project/{project-dir}/node_modules
+    // - NODE_PATH allows resolving from project node_modules first, then base node_modules
+    let node_path = format!(
+        "/project/{}/node_modules:/home/user/node_modules",
+        params.project_dir
+    );
+
+    render_dockerfile(
+        template,
+        context! {
+            PROJECT_TYPE => "nodejs",
+            WORKDIR => params.project_dir,
+            NODE_PATH => node_path,
+            PACKAGE_MANAGER_INSTALL => package_manager_install,
+            BUILD_COMMAND => build_command,
+            ..dockerfile_context(params)
+        },
+    )
+}
+
+/// Render the Dockerfile template for a Node.js project running on Bun
+/// The dependencies are installed with `bun install` and the entrypoint runs with `bun run`.
+fn modify_dockerfile_bun(
+    template: &str,
+    params: &DockerfileParams,
+) -> Result<String, minijinja::Error> {
+    render_dockerfile(
+        template,
+        context! {
+            PROJECT_TYPE => "nodejs",
+            ..dockerfile_context(params)
+        },
+    )
+}
+
+/// Render the multi-stage Dockerfile template for a Rust project
+/// The binary is built with cargo in a builder stage and copied into the base image.
+fn modify_dockerfile_rust(
+    template: &str,
+    params: &DockerfileParams,
+) -> Result<String, minijinja::Error> {
+    render_dockerfile(
+        template,
+        context! {
+            PROJECT_TYPE => "rust",
+            BUILDER_IMAGE => RUST_BUILDER_IMAGE,
+            ..dockerfile_context(params)
+        },
+    )
+}
 
-        if !copy_status.success() {
-            return Err("Failed to copy project.tar.gz from container".into());
-        }
+/// Render the Dockerfile template for a Deno project
+/// The dependencies are installed at build time; the entrypoint module is run with network,
+/// environment and read access, or without one the project's `start` task.
+fn modify_dockerfile_deno(
+    template: &str,
+    params: &DockerfileParams,
+) -> Result<String, minijinja::Error> {
+    render_dockerfile(
+        template,
+        context! {
+            PROJECT_TYPE => "deno",
+            ..dockerfile_context(params)
+        },
+    )
+}
 
-        // Remove the container
-        println!("Removing container...");
-        let rm_status = Command::new("docker")
-            .args(["rm", &container_id])
-            .status()?;
+/// Render a user-supplied Dockerfile template (--dockerfile). Jinja syntax is rendered
+/// like the built-in templates, then the {BASE_IMAGE}, {PROJECT_DIR}, {PORT}, {ENTRYPOINT}
+/// and {PYTHON_VERSION} placeholders are substituted. A plain Dockerfile is kept as it is.
+fn modify_dockerfile_user(
+    template: &str,
+    params: &DockerfileParams,
+    project_type: &str,
+    python_version: Option<&str>,
+) -> Result<String, minijinja::Error> {
+    let rendered = if template.contains("{{") || template.contains("{%") {
+        render_dockerfile(
+            template,
+            context! {
+                PROJECT_TYPE => project_type,
+                WORKDIR => params.project_dir,
+                PYTHON_VERSION => python_version,
+                ..dockerfile_context(params)
+            },
+        )?
+    } else {
+        template.to_string()
+    };
 
-        if !rm_status.success() {
-            return Err("Failed to remove container".into());
+    let mut placeholders = vec![
+        ("BASE_IMAGE".to_string(), params.base_image.to_string()),
+        ("PROJECT_DIR".to_string(), params.project_dir.to_string()),
+        ("PORT".to_string(), params.port.to_string()),
+        ("ENTRYPOINT".to_string(), params.entrypoint.to_string()),
+    ];
+    if let Some(python_version) = python_version {
+        placeholders.push(("PYTHON_VERSION".to_string(), python_version.to_string()));
+    }
+    // ${PORT} and the like are variables of the Dockerfile itself
+    Ok(placeholders.iter().fold(rendered, |result, (key, value)| {
+        let placeholder = format!("{{{}}}", key);
+        let mut substituted = String::with_capacity(result.len());
+        let mut rest = result.as_str();
+        while let Some(index) = rest.find(&placeholder) {
+            substituted.push_str(&rest[..index]);
+            if rest[..index].ends_with('$') {
+                substituted.push_str(&placeholder);
+            } else {
+                substituted.push_str(value);
+            }
+            rest = &rest[index + placeholder.len()..];
         }
+        substituted.push_str(rest);
+        substituted
+    }))
+}
 
-        if tar_file_path.exists() {
-            println!(
-                "✓ project.tar.gz created successfully: {}",
-                tar_file_path.display()
-            );
-        } else {
-            return Err(format!("project.tar.gz not found at: {}", tar_file_path.display()).into());
-        }
+/// Template variables available in every Dockerfile template
+fn dockerfile_context(params: &DockerfileParams) -> minijinja::Value {
+    context! {
+        BASE_IMAGE => params.base_image,
+        PROJECT_DIR => params.project_dir,
+        PORT => params.port,
+        ENTRYPOINT => params.entrypoint,
+        ENV_VARS => params.env_vars,
+        LABELS => label_block(params.labels),
+        HEALTHCHECK => healthcheck_directive(params),
+        BUILD_INFO => params.build_info,
     }
+}
 
-    // Generate Helm chart
-    println!("\n=== Generating Helm Chart ===");
-    let (service_name, version) = match project_type.as_str() {
-        "python" => {
-            // Extract service name and version from pyproject.toml
-            let (name, ver) = read_service_info_from_pyproject(project_home)?;
-            println!("Service name from pyproject.toml: {}", name);
-            println!("Version from pyproject.toml: {}", ver);
-            (name, ver)
-        }
-        "nodejs" => {
-            // Extract service name and version from package.json
-            let (name, ver) = read_service_info_from_package_json(project_home)?;
-            println!("Service name from package.json: {}", name);
-            println!("Version from package.json: {}", ver);
-            (name, ver)
-        }
-        _ => return Err("Unsupported project type for Helm chart generation".into()),
-    };
+/// Render a Dockerfile template with minijinja
+/// Block tags do not leave empty lines behind, and using an undefined
+/// variable is an error rather than silently rendering nothing.
+fn render_dockerfile(template: &str, ctx: minijinja::Value) -> Result<String, minijinja::Error> {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.set_keep_trailing_newline(true);
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.render_str(template, ctx)
+}
 
-    let chart_dir = temp_dir.join(&service_name);
+/// Render image labels as a single Dockerfile LABEL instruction
+fn label_block(labels: &[(String, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let entries: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{}=\"{}\"",
+                key,
+                value.replace('\\', "\\\\").replace('"', "\\\"")
+            )
+        })
+        .collect();
+    format!("LABEL {}", entries.join(" \\\n      "))
+}
 
-    println!("Generating charts template in {}", chart_dir.display());
-    copy_and_replace_charts(&chart_dir, &service_name, &version, port, image_name)?;
+/// Render the Dockerfile HEALTHCHECK instruction, or nothing without a health path
+fn healthcheck_directive(params: &DockerfileParams) -> String {
+    match params.health_path {
+        Some(health_path) => format!(
+            "HEALTHCHECK CMD curl -fsS http://localhost:{}{} || exit 1",
+            params.port, health_path
+        ),
+        None => String::new(),
+    }
+}
 
-    // Run helm lint
-    println!("\nRunning helm lint...");
-    let lint_status = Command::new("helm")
-        .args(["lint", chart_dir.to_str().unwrap()])
-        .status()?;
+/// Turn a project name such as `My_Service` or `@org/pkg` into an RFC 1123 label
+/// (lowercase letters, digits and '-', at most 63 characters), or None if nothing is left.
+/// The scope of a scoped npm package name is dropped.
+fn sanitize_service_name(name: &str) -> Option<String> {
+    let unscoped = name
+        .strip_prefix('@')
+        .and_then(|scoped| scoped.split_once('/'))
+        .map_or(name, |(_, package)| package);
+    let mut sanitized = String::new();
+    for c in unscoped.chars() {
+        if c.is_ascii_alphanumeric() {
+            sanitized.push(c.to_ascii_lowercase());
+        } else if !sanitized.is_empty() && !sanitized.ends_with('-') {
+            sanitized.push('-');
+        }
+    }
+    sanitized.truncate(63);
+    let sanitized = sanitized.trim_end_matches('-');
+    (!sanitized.is_empty()).then(|| sanitized.to_string())
+}
 
-    if !lint_status.success() {
-        return Err("Helm lint failed".into());
+/// Parse a port number for --port and the prompts, rejecting 0
+fn parse_port(value: &str) -> Result<u16, String> {
+    match value.trim().parse::<u16>() {
+        Ok(0) | Err(_) => Err(format!(
+            "Invalid port number '{}': expected 1-65535",
+            value.trim()
+        )),
+        Ok(port) => Ok(port),
     }
+}
 
-    println!("✓ Helm lint passed");
+/// Parse a version for --project-version: SemVer with an optional leading `v`, such as
+/// `1.2.3`, `v1.2.3-rc.1` or `1.2.3-4-gabc1234` from `git describe`
+fn parse_project_version(value: &str) -> Result<String, String> {
+    let version = value.trim();
+    let (core, build) = match version.split_once('+') {
+        Some((core, build)) => (core, Some(build)),
+        None => (version, None),
+    };
+    let (core, pre_release) = match core.split_once('-') {
+        Some((core, pre_release)) => (core, Some(pre_release)),
+        None => (core, None),
+    };
+    let identifiers = |s: &str| {
+        s.split('.')
+            .all(|i| !i.is_empty() && i.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+    };
+    let numbers = core
+        .strip_prefix('v')
+        .unwrap_or(core)
+        .split('.')
+        .collect::<Vec<_>>();
+    let valid = numbers.len() == 3
+        && numbers
+            .iter()
+            .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        && pre_release.is_none_or(identifiers)
+        && build.is_none_or(identifiers);
+    if valid {
+        Ok(version.to_string())
+    } else {
+        Err(format!(
+            "Invalid version '{}': expected a SemVer version such as 1.2.3 or 1.2.3-rc.1",
+            version
+        ))
+    }
+}
 
-    // Run helm package
-    println!("\nRunning helm package...");
-    let package_status = Command::new("helm")
-        .args(["package", chart_dir.to_str().unwrap()])
-        .current_dir(&temp_dir)
-        .status()?;
+/// Parse a KEY=VALUE command line entry
+fn parse_key_value(entry: &str, flag: &str) -> Result<(String, String), ServiceMakerError> {
+    match entry.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!("Invalid {} entry '{}': expected KEY=VALUE", flag, entry),
+        )),
+    }
+}
 
-    if !package_status.success() {
-        return Err("Helm package failed".into());
+/// Check a Kubernetes label or annotation key: an optional DNS subdomain prefix and
+/// `/`, followed by a name
+fn validate_k8s_key(key: &str, flag: &str) -> Result<(), ServiceMakerError> {
+    let (prefix, name) = match key.split_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, key),
+    };
+    let valid_prefix = prefix.is_none_or(|prefix| {
+        !prefix.is_empty()
+            && prefix.len() <= 253
+            && prefix.split('.').all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                    && !part.starts_with('-')
+                    && !part.ends_with('-')
+            })
+    });
+    if !valid_prefix || !is_k8s_name(name) {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Config,
+            format!(
+                "Invalid {} key '{}': expected [PREFIX/]NAME with a NAME of at most 63 \
+                 letters, digits, '-', '_' and '.', starting and ending with a letter or digit",
+                flag, key
+            ),
+        ));
     }
+    Ok(())
+}
 
-    // Find the generated chart file
-    let chart_file_name = format!("{}-{}.tgz", service_name, version);
-    let chart_file_path = temp_dir.join(&chart_file_name);
+/// Whether `name` is a DNS label (RFC 1123), the format of namespace and release names
+fn is_dns_label(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 63
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+}
 
-    if chart_file_path.exists() {
-        println!(
-            "✓ Helm chart packaged successfully: {}",
-            chart_file_path.display()
-        );
-        println!("\nGenerated Helm chart: {}", chart_file_name);
+/// Whether `name` is a valid Kubernetes label name (also the format of label values)
+fn is_k8s_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 63
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric())
+}
+
+/// Replace `{KEY}` placeholders with the given template variables
+fn substitute_template_vars(content: &str, template_vars: &[(String, String)]) -> String {
+    template_vars
+        .iter()
+        .fold(content.to_string(), |result, (key, value)| {
+            result.replace(&format!("{{{}}}", key), value)
+        })
+}
+
+/// Read template variables from a TOML or (by extension) JSON file of key-value pairs
+fn read_template_vars_file(
+    vars_file: &Path,
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(vars_file).map_err(|e| {
+        format!(
+            "Failed to read template variables file {}: {}",
+            vars_file.display(),
+            e
+        )
+    })?;
+
+    let is_json = vars_file
+        .extension()
+        .is_some_and(|extension| extension == "json");
+    let entries: Vec<(String, Option<String>)> = if is_json {
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid JSON in {}: {}", vars_file.display(), e))?;
+        let object = value.as_object().ok_or_else(|| {
+            format!(
+                "Template variables file {} must contain a JSON object",
+                vars_file.display()
+            )
+        })?;
+        object
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) => Some(s.clone()),
+                    serde_json::Value::Number(n) => Some(n.to_string()),
+                    serde_json::Value::Bool(b) => Some(b.to_string()),
+                    _ => None,
+                };
+                (key.clone(), value)
+            })
+            .collect()
     } else {
-        return Err(format!("Helm chart file not found: {}", chart_file_path.display()).into());
+        let table: toml::Table = toml::from_str(&content)
+            .map_err(|e| format!("Invalid TOML in {}: {}", vars_file.display(), e))?;
+        table
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::String(s) => Some(s.clone()),
+                    Value::Integer(i) => Some(i.to_string()),
+                    Value::Float(f) => Some(f.to_string()),
+                    Value::Boolean(b) => Some(b.to_string()),
+                    _ => None,
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    };
+
+    let mut template_vars = Vec::new();
+    for (key, value) in entries {
+        let value = value.ok_or_else(|| {
+            format!(
+                "Template variable '{}' in {} must be a string, number or boolean",
+                key,
+                vars_file.display()
+            )
+        })?;
+        template_vars.push((key, value));
     }
+    Ok(template_vars)
+}
 
-    println!("\nTemporary directory: {}", temp_dir.display());
-    println!("(Note: Temporary directory is left behind for inspection)");
+/// Determine the URL of the project's git remote "origin", if any
+/// SSH remotes (git@host:org/repo.git) are turned into https URLs and
+/// credentials embedded in https URLs are removed.
+fn detect_git_remote(project_home: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_home)
+        .args(["remote", "get-url", "origin"])
+        .logged()
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
 
-    Ok(())
-}
+    let remote = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if remote.is_empty() {
+        return None;
+    }
 
-fn prompt(message: &str) -> Result<String, io::Error> {
-    print!("{}: ", message);
-    io::stdout().flush()?;
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    Ok(input.trim().to_string())
-}
+    if let Some(rest) = remote.strip_prefix("git@")
+        && let Some((host, path)) = rest.split_once(':')
+    {
+        return Some(format!("https://{}/{}", host, path));
+    }
 
-fn extract_python_version(base_image: &str) -> String {
-    if let Some(py_pos) = base_image.find("py") {
-        let after_py = &base_image[py_pos + 2..];
-        if let Some(end_pos) = after_py.find(|c: char| !c.is_ascii_digit()) {
-            let version_digits = &after_py[..end_pos];
-            if !version_digits.is_empty() {
-                // Convert "12" -> "3.12", etc.
-                return format!("3.{}", version_digits);
-            }
-        } else if !after_py.is_empty() && after_py.chars().all(|c| c.is_ascii_digit()) {
-            // Handle case where version digits extend to end of string
-            return format!("3.{}", after_py);
-        }
+    if let Some((scheme, rest)) = remote.split_once("://")
+        && let Some((_credentials, host_and_path)) = rest.split_once('@')
+    {
+        return Some(format!("{}://{}", scheme, host_and_path));
     }
-    // Default fallback if pattern not found
-    "3.12".to_string()
+
+    Some(remote)
 }
 
-fn modify_dockerfile_python(
-    template: &str,
-    base_image: &str,
-    project_dir: &str,
-    entrypoint: &str,
-    port: u16,
-    python_version: &str,
-    env_vars: &[(String, String)],
-) -> String {
-    let mut result = template
-        .replace("{BASE_IMAGE}", base_image)
-        .replace("{PROJECT_DIR}", project_dir)
-        .replace("{PORT}", &port.to_string())
-        .replace("{ENTRYPOINT}", entrypoint)
-        .replace("{PYTHON_VERSION}", python_version);
-    
-    // Add environment variables if any
-    if !env_vars.is_empty() {
-        let env_lines: Vec<String> = env_vars
-            .iter()
-            .map(|(key, value)| format!("ENV {}={}", key, value))
-            .collect();
-        let env_block = format!("\n{}", env_lines.join("\n"));
-        
-        // Insert after WORKDIR line
-        if let Some(pos) = result.find("WORKDIR")
-            && let Some(newline_pos) = result[pos..].find('\n') {
-            let insert_pos = pos + newline_pos + 1;
-            result.insert_str(insert_pos, &env_block);
-        }
+/// Determine the commit checked out in the project's git repository, if any
+fn detect_git_commit(project_home: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_home)
+        .args(["rev-parse", "HEAD"])
+        .logged()
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
     }
-    
-    result
+
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!commit.is_empty()).then_some(commit)
 }
 
-/// Modify Node.js Dockerfile template with project-specific values
-/// Sets up NODE_PATH to resolve from project node_modules first, then base node_modules
-fn modify_dockerfile_nodejs(
-    template: &str,
-    base_image: &str,
-    project_dir: &str,
-    entrypoint: &str,
-    port: u16,
-    env_vars: &[(String, String)],
-) -> String {
-    // Node.js app structure:
-    // - COPY copies the project directory directly
-    // - WORKDIR is /project/{project-dir}
-    // - node_modules is in /project/{project-dir}/node_modules
-    // - NODE_PATH allows resolving from project node_modules first, then base node_modules
-    let node_path = format!("/project/{}/node_modules:/home/user/node_modules", project_dir);
-    
-    let mut result = template
-        .replace("{BASE_IMAGE}", base_image)
-        .replace("{PROJECT_DIR}", project_dir)
-        .replace("{WORKDIR}", project_dir)
-        .replace("{ENTRYPOINT}", entrypoint)
-        .replace("{PORT}", &port.to_string())
-        .replace("{NODE_PATH}", &node_path);
-    
-    // Add environment variables if any
-    if !env_vars.is_empty() {
-        let env_lines: Vec<String> = env_vars
-            .iter()
-            .map(|(key, value)| format!("ENV {}={}", key, value))
-            .collect();
-        let env_block = format!("\n{}", env_lines.join("\n"));
-        
-        // Insert after NODE_PATH ENV line
-        if let Some(pos) = result.find("ENV NODE_PATH")
-            && let Some(newline_pos) = result[pos..].find('\n') {
-            let insert_pos = pos + newline_pos + 1;
-            result.insert_str(insert_pos, &env_block);
-        }
+/// Short random hex string, e.g. for unique directory names
+fn random_suffix() -> String {
+    use std::hash::{BuildHasher, Hasher};
+
+    // RandomState is seeded randomly per process, the time differs between calls
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    if let Ok(elapsed) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
     }
-    
-    result
+    format!("{:08x}", hasher.finish() as u32)
+}
+
+/// Format a point in time as an RFC 3339 UTC timestamp, e.g. 2026-06-24T12:00:00Z
+fn format_utc_timestamp(time: std::time::SystemTime) -> String {
+    let secs = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+
+    // Civil date from days since 1970-01-01 (proleptic Gregorian calendar)
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
 }
 
 /// Read environment variables from .env.example file
 /// Parses KEY=VALUE format and handles quoted values
-fn read_env_example(project_home: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+fn read_env_example(
+    project_home: &Path,
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
     let env_example_path = project_home.join(".env.example");
-    
+
     if !env_example_path.exists() {
         return Ok(Vec::new());
     }
-    
+
     let content = fs::read_to_string(&env_example_path)?;
     let mut env_vars = Vec::new();
-    
+
     for line in content.lines() {
         let line = line.trim();
-        
+
         // Skip empty lines and comments
         if line.is_empty() || line.starts_with('#') {
             continue;
         }
-        
+
         // Parse KEY=VALUE format
         if let Some(equal_pos) = line.find('=') {
             let key = line[..equal_pos].trim().to_string();
             let mut value = line[equal_pos + 1..].trim().to_string();
-            
+
             // Remove quotes if present (handles both single and double quotes)
-            if (value.starts_with('"') && value.ends_with('"')) 
-                || (value.starts_with('\'') && value.ends_with('\'')) {
+            if (value.starts_with('"') && value.ends_with('"'))
+                || (value.starts_with('\'') && value.ends_with('\''))
+            {
                 value = value[1..value.len() - 1].to_string();
             }
-            
+
             // Skip if key is empty
             if !key.is_empty() {
                 // If value contains spaces or special characters, quote it for Docker ENV
-                let final_value = if value.contains(' ') || value.contains('$') || value.contains('\\') {
-                    format!("\"{}\"", value.replace('"', "\\\""))
-                } else {
-                    value
-                };
+                let final_value =
+                    if value.contains(' ') || value.contains('$') || value.contains('\\') {
+                        format!("\"{}\"", value.replace('"', "\\\""))
+                    } else {
+                        value
+                    };
                 env_vars.push((key, final_value));
             }
         }
     }
-    
+
     Ok(env_vars)
 }
 
+/// Read KEY=VALUE entries from a dotenv file for the chart's ConfigMap
+/// Unlike `.env.example` entries, malformed lines and duplicate keys are errors.
+fn read_env_file(env_file: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(env_file)
+        .map_err(|e| format!("Failed to read env file {}: {}", env_file.display(), e))?;
+    let mut entries: Vec<(String, String)> = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        // Skip empty lines and comments
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = parse_key_value(line, "--env-file").map_err(|_| {
+            format!(
+                "Invalid line {} in {}: expected KEY=VALUE",
+                index + 1,
+                env_file.display()
+            )
+        })?;
+        let mut value = value.trim().to_string();
+
+        // Remove quotes if present (handles both single and double quotes)
+        if value.len() >= 2
+            && ((value.starts_with('"') && value.ends_with('"'))
+                || (value.starts_with('\'') && value.ends_with('\'')))
+        {
+            value = value[1..value.len() - 1].to_string();
+        }
+
+        if entries.iter().any(|(existing, _)| *existing == key) {
+            return Err(format!(
+                "Duplicate key '{}' in {} (line {})",
+                key,
+                env_file.display(),
+                index + 1
+            )
+            .into());
+        }
+        entries.push((key, value));
+    }
+
+    Ok(entries)
+}
+
 /// Detect Node.js entrypoint from package.json
 /// Checks "main" field first, then "start" script
-fn detect_nodejs_entrypoint(project_home: &Path) -> Result<Option<String>, Box<dyn std::error::Error>> {
+fn detect_nodejs_entrypoint(
+    project_home: &Path,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
     let package_json_path = project_home.join("package.json");
-    
+
     if !package_json_path.exists() {
         return Ok(None);
     }
-    
+
     let content = fs::read_to_string(&package_json_path)?;
     let value: serde_json::Value = serde_json::from_str(&content)?;
-    
+
     // Try to get from "main" field
     if let Some(main) = value.get("main").and_then(|m| m.as_str()) {
         return Ok(Some(main.to_string()));
     }
-    
+
     // Try to extract from "start" script
     if let Some(scripts) = value.get("scripts")
-        && let Some(start) = scripts.get("start").and_then(|s| s.as_str()) {
-        // Extract the script name from "node index.js" or "node app.js"
-        if let Some(script_name) = start.strip_prefix("node ") {
+        && let Some(start) = scripts.get("start").and_then(|s| s.as_str())
+    {
+        // Extract the script name from "node index.js", "bun run index.ts" or "bun index.ts"
+        if let Some(script_name) = ["node ", "bun run ", "bun "]
+            .iter()
+            .find_map(|prefix| start.strip_prefix(prefix))
+        {
             return Ok(Some(script_name.trim().to_string()));
         }
     }
-    
+
     Ok(None)
 }
 
-/// Detect project type: "python" or "nodejs"
+/// Detect the package manager of a Node.js project from its lockfile
+fn detect_package_manager(project_home: &Path) -> &'static str {
+    if project_home.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if project_home.join("yarn.lock").exists() {
+        "yarn"
+    } else {
+        "npm"
+    }
+}
+
+/// The install command of the package manager, run by prepareproject-nodejs.sh instead of
+/// installing only the packages missing in the base image. A lockfile is installed exactly,
+/// which that cannot do, so only npm without package-lock.json keeps the default (None).
+/// yarn and pnpm are not in the base image and run through corepack.
+fn package_manager_install(project_home: &Path, package_manager: &str) -> Option<String> {
+    let command = match package_manager {
+        "yarn" if project_home.join("yarn.lock").exists() => {
+            "corepack yarn install --frozen-lockfile --production"
+        }
+        "yarn" => "corepack yarn install --production",
+        "pnpm" if project_home.join("pnpm-lock.yaml").exists() => {
+            "corepack pnpm install --frozen-lockfile --prod"
+        }
+        "pnpm" => "corepack pnpm install --prod",
+        _ if project_home.join("package-lock.json").exists() => "npm ci --omit=dev",
+        _ => return None,
+    };
+    Some(command.to_string())
+}
+
+/// The command compiling a TypeScript project with its package.json `script`. It needs the
+/// dev dependencies, which the production install that follows does not have.
+fn package_manager_build(project_home: &Path, package_manager: &str, script: &str) -> String {
+    match package_manager {
+        "yarn" if project_home.join("yarn.lock").exists() => format!(
+            "corepack yarn install --frozen-lockfile && corepack yarn run {}",
+            script
+        ),
+        "yarn" => format!("corepack yarn install && corepack yarn run {}", script),
+        "pnpm" if project_home.join("pnpm-lock.yaml").exists() => format!(
+            "corepack pnpm install --frozen-lockfile && corepack pnpm run {}",
+            script
+        ),
+        "pnpm" => format!("corepack pnpm install && corepack pnpm run {}", script),
+        _ if project_home.join("package-lock.json").exists() => {
+            format!("npm ci && npm run {}", script)
+        }
+        _ => format!("npm install --no-save && npm run {}", script),
+    }
+}
+
+/// Whether package.json defines the script `name`
+fn package_json_has_script(project_home: &Path, name: &str) -> bool {
+    fs::read_to_string(project_home.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .is_some_and(|value| value.get("scripts").and_then(|s| s.get(name)).is_some())
+}
+
+/// compilerOptions.outDir of tsconfig.json (which may contain comments), if set
+fn typescript_out_dir(project_home: &Path) -> Result<Option<String>, ServiceMakerError> {
+    Ok(read_tsconfig(project_home)?
+        .pointer("/compilerOptions/outDir")
+        .and_then(|o| o.as_str())
+        .map(|o| o.trim_start_matches("./").trim_end_matches('/').to_string()))
+}
+
+/// Read tsconfig.json of a TypeScript project
+fn read_tsconfig(project_home: &Path) -> Result<serde_json::Value, ServiceMakerError> {
+    let path = project_home.join("tsconfig.json");
+    fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&strip_jsonc(&content)).map_err(|e| e.to_string()))
+        .map_err(|e| {
+            ServiceMakerError::new(
+                ErrorKind::Config,
+                format!("Failed to read {}: {}", path.display(), e),
+            )
+        })
+}
+
+/// Where tsc writes `<stem>.ts`: below the output directory, relative to compilerOptions.rootDir
+/// if set (else the file is assumed to be relative to the project's source root `src/`)
+fn compiled_entrypoint(project_home: &Path, out_dir: &str, stem: &str) -> String {
+    let root_dir = read_tsconfig(project_home)
+        .ok()
+        .and_then(|tsconfig| {
+            tsconfig
+                .pointer("/compilerOptions/rootDir")
+                .and_then(|r| r.as_str())
+                .map(|r| r.trim_start_matches("./").trim_end_matches('/').to_string())
+        })
+        .unwrap_or_else(|| "src".to_string());
+    let stem = stem.trim_start_matches("./");
+    let relative = stem
+        .strip_prefix(&format!("{}/", root_dir))
+        .filter(|_| !root_dir.is_empty() && root_dir != ".")
+        .unwrap_or(stem);
+    format!("{}/{}.js", out_dir, relative)
+}
+
+/// Whether a Node.js project uses Bun: it has a bun.lockb (or the text bun.lock)
+fn has_bun_lockfile(project_home: &Path) -> bool {
+    project_home.join("bun.lockb").exists() || project_home.join("bun.lock").exists()
+}
+
+/// Detect project type: "python", "nodejs", "rust" or "deno"
 /// Python: has pyproject.toml
 /// Node.js: has package.json (and no services.json or manifest.json)
 fn detect_project_type(project_home: &Path) -> Result<String, Box<dyn std::error::Error>> {
@@ -683,24 +5641,106 @@ fn detect_project_type(project_home: &Path) -> Result<String, Box<dyn std::error
     } else if package_json.exists() {
         // Node.js project: must not have services.json or manifest.json (those are not supported)
         if services_json.exists() || manifest_json.exists() {
-            return Err(format!(
-                "Node.js projects with services.json or manifest.json are not supported. \
-                This service only supports Python projects and simple Node.js projects with package.json only. \
-                Found in: {}",
-                project_home.display()
-            ).into());
+            return Err(ServiceMakerError::new(
+                ErrorKind::Detection,
+                format!(
+                    "Node.js projects with services.json or manifest.json are not supported. \
+                    This service only supports Python projects and simple Node.js projects with package.json only. \
+                    Found in: {}",
+                    project_home.display()
+                ),
+            )
+            .into());
         }
         // Simple Node.js project
         Ok("nodejs".to_string())
+    } else if project_home.join("Cargo.toml").exists() {
+        // Rust project detected
+        Ok("rust".to_string())
+    } else if has_setuptools_manifest(project_home) {
+        // Older Python project with setup.py / setup.cfg
+        Ok("python".to_string())
+    } else if deno_config_path(project_home).is_some() {
+        // Deno project detected (a package.json next to it makes it a Node.js project)
+        Ok("deno".to_string())
     } else {
-        Err(format!(
-            "Could not detect project type. Expected pyproject.toml (Python) or package.json (Node.js) in: {}",
-            project_home.display()
+        Err(ServiceMakerError::new(
+            ErrorKind::Detection,
+            format!(
+                "Could not detect project type. Expected pyproject.toml, setup.py or setup.cfg (Python), package.json (Node.js), Cargo.toml (Rust) or deno.json (Deno) in: {}",
+                project_home.display()
+            ),
         )
         .into())
     }
 }
 
+/// Detect the project type, falling back to an external detector script
+/// (if given) when none of the built-in rules match
+fn detect_project_type_with_script(
+    project_home: &Path,
+    detector_script: Option<&Path>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let detection_error = match detect_project_type(project_home) {
+        Ok(project_type) => return Ok(project_type),
+        Err(e) => e,
+    };
+
+    let Some(script) = detector_script else {
+        return Err(detection_error);
+    };
+
+    debug!("Running detector script: {}", script.display());
+    let output = Command::new(script)
+        .arg(project_home)
+        .logged()
+        .output()
+        .map_err(|e| {
+            ServiceMakerError::new(
+                ErrorKind::Detection,
+                format!("Failed to run detector script {}: {}", script.display(), e),
+            )
+        })?;
+
+    if !output.status.success() {
+        return Err(ServiceMakerError::new(
+            ErrorKind::Detection,
+            format!(
+                "Detector script {} failed with exit code {:?}: {}",
+                script.display(),
+                output.status.code(),
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        )
+        .into());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let project_type = stdout.lines().next().unwrap_or("").trim().to_string();
+    match project_type.as_str() {
+        t if PROJECT_TYPES.contains(&t) => Ok(project_type),
+        "" => Err(ServiceMakerError::new(
+            ErrorKind::Detection,
+            format!(
+                "Detector script {} did not print a project type",
+                script.display()
+            ),
+        )
+        .into()),
+        _ => Err(ServiceMakerError::new(
+            ErrorKind::Detection,
+            format!(
+                "Detector script {} returned unsupported project type '{}'. \
+                Expected one of: {}",
+                script.display(),
+                project_type,
+                PROJECT_TYPES.join(", ")
+            ),
+        )
+        .into()),
+    }
+}
+
 fn read_name_from_package_json(project_home: &Path) -> Result<String, Box<dyn std::error::Error>> {
     let package_json_path = project_home.join("package.json");
 
@@ -718,38 +5758,262 @@ fn read_name_from_package_json(project_home: &Path) -> Result<String, Box<dyn st
         .ok_or("Missing 'name' in package.json")?
         .to_string();
 
-    Ok(name)
+    Ok(name)
+}
+
+/// Hash the contents of every file (and symlink target) below `dir`, keyed by relative path
+fn snapshot_dir(dir: &Path) -> io::Result<BTreeMap<PathBuf, u64>> {
+    fn visit(root: &Path, dir: &Path, snapshot: &mut BTreeMap<PathBuf, u64>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let file_type = fs::symlink_metadata(&path)?.file_type();
+            let mut hasher = std::hash::DefaultHasher::new();
+            if file_type.is_dir() {
+                visit(root, &path, snapshot)?;
+                continue;
+            } else if file_type.is_symlink() {
+                fs::read_link(&path)?.hash(&mut hasher);
+            } else {
+                fs::read(&path)?.hash(&mut hasher);
+            }
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            snapshot.insert(relative, hasher.finish());
+        }
+        Ok(())
+    }
+
+    let mut snapshot = BTreeMap::new();
+    visit(dir, dir, &mut snapshot)?;
+    Ok(snapshot)
+}
+
+/// Compare `dir` with an earlier snapshot and fail listing every added, removed or modified file
+fn check_unchanged(
+    dir: &Path,
+    before: &BTreeMap<PathBuf, u64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let after = snapshot_dir(dir)?;
+    let mut changes = Vec::new();
+    for (path, hash) in before {
+        match after.get(path) {
+            None => changes.push(format!("removed {}", path.display())),
+            Some(new_hash) if new_hash != hash => {
+                changes.push(format!("modified {}", path.display()))
+            }
+            Some(_) => {}
+        }
+    }
+    for path in after.keys().filter(|path| !before.contains_key(*path)) {
+        changes.push(format!("added {}", path.display()));
+    }
+
+    if changes.is_empty() {
+        Ok(())
+    } else {
+        Err(ServiceMakerError::new(
+            ErrorKind::Internal,
+            format!("Project home was modified: {}", changes.join(", ")),
+        )
+        .with_context(dir.display().to_string())
+        .into())
+    }
+}
+
+/// How the project is copied into the build context
+struct CopyOptions<'a> {
+    /// --reflink policy
+    reflink: &'a str,
+    follow_symlinks: bool,
+    /// Names, or paths relative to the project, which are not copied
+    excludes: &'a [String],
+}
+
+/// Whether the project-relative `path` matches one of the --exclude entries
+fn is_excluded(path: &Path, excludes: &[String]) -> bool {
+    excludes.iter().any(|exclude| {
+        let exclude = exclude.trim_start_matches("./").trim_matches('/');
+        if exclude.contains('/') {
+            path == Path::new(exclude)
+        } else {
+            path.file_name().is_some_and(|name| name == exclude)
+        }
+    })
+}
+
+/// Copy the project at `src` to `dst`. Symlinks are skipped with a warning unless
+/// `follow_symlinks` is set; then the directories on the current path and the copies are
+/// tracked by (device, inode), so a link to a parent directory or to `/` cannot loop forever.
+fn copy_dir_recursive(src: &Path, dst: &Path, options: &CopyOptions) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    fn visit(
+        src: &Path,
+        dst: &Path,
+        relative: &Path,
+        options: &CopyOptions,
+        visited: &mut HashSet<(u64, u64)>,
+    ) -> io::Result<()> {
+        if !dst.exists() {
+            fs::create_dir_all(dst)?;
+        }
+        // A link to a parent may reach the copy, which must not be copied into itself
+        let copy = fs::metadata(dst)?;
+        visited.insert((copy.dev(), copy.ino()));
+
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_name = entry.file_name();
+
+            // Skip .venv directories (Python) and node_modules (Node.js)
+            if SKIPPED_DIRS.iter().any(|skipped| file_name == *skipped) {
+                continue;
+            }
+            let relative_path = relative.join(&file_name);
+            if is_excluded(&relative_path, options.excludes) {
+                debug!("Excluding {}", relative_path.display());
+                continue;
+            }
+
+            let dest_path = dst.join(&file_name);
+
+            let metadata = if entry.file_type()?.is_symlink() {
+                if !options.follow_symlinks {
+                    warn!(
+                        "Skipping symlink {} (use --follow-symlinks to copy its target)",
+                        path.display()
+                    );
+                    continue;
+                }
+                match fs::metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        warn!("Skipping broken symlink {}: {}", path.display(), e);
+                        continue;
+                    }
+                }
+            } else {
+                entry.metadata()?
+            };
+
+            if metadata.is_dir() {
+                // Only a followed symlink can lead back to a visited directory
+                let id = (metadata.dev(), metadata.ino());
+                if !visited.insert(id) {
+                    warn!(
+                        "Skipping symlink {}, which loops back to a directory being copied",
+                        path.display()
+                    );
+                    continue;
+                }
+                visit(&path, &dest_path, &relative_path, options, visited)?;
+                visited.remove(&id);
+            } else {
+                copy_file(&path, &dest_path, options.reflink)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    let root = fs::metadata(src)?;
+    let mut visited = HashSet::from([(root.dev(), root.ino())]);
+    visit(src, dst, Path::new(""), options, &mut visited)
+}
+
+/// Copy a file according to the --reflink policy: "always" clones it as a
+/// copy-on-write reflink, "auto" tries that and falls back to a regular copy.
+/// The permission bits are carried over, so executable scripts stay executable
+/// (a reflinked file is created with the default mode).
+fn copy_file(src: &Path, dst: &Path, reflink: &str) -> io::Result<()> {
+    match reflink {
+        "never" => fs::copy(src, dst).map(|_| ()),
+        "always" => reflink_file(src, dst).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!(
+                    "Failed to reflink {} (--reflink always): {}",
+                    src.display(),
+                    e
+                ),
+            )
+        }),
+        _ => reflink_file(src, dst).or_else(|_| fs::copy(src, dst).map(|_| ())),
+    }?;
+    fs::set_permissions(dst, fs::metadata(src)?.permissions())
+}
+
+/// Clone `src` to `dst` with the FICLONE ioctl, which shares the data blocks on
+/// copy-on-write filesystems such as Btrfs and XFS
+#[cfg(target_os = "linux")]
+fn reflink_file(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let source = fs::File::open(src)?;
+    let target = fs::File::create(dst)?;
+    // SAFETY: both file descriptors are open for the duration of the call
+    let result = unsafe { libc::ioctl(target.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) };
+    if result != 0 {
+        let error = io::Error::last_os_error();
+        drop(target);
+        let _ = fs::remove_file(dst);
+        return Err(error);
+    }
+    fs::set_permissions(dst, source.metadata()?.permissions())
 }
 
-fn copy_dir_recursive(src: &Path, dst: &Path) -> io::Result<()> {
-    if !dst.exists() {
-        fs::create_dir_all(dst)?;
+#[cfg(not(target_os = "linux"))]
+fn reflink_file(_src: &Path, _dst: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reflinks are only supported on Linux",
+    ))
+}
+
+fn find_single_py_file(project_home: &Path) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    // setup.py of a setuptools project is never the entrypoint
+    let mut py_files = list_root_files(project_home, &["py"])?;
+    py_files.retain(|file| file != "setup.py");
+
+    // Return the filename if exactly one .py file is found
+    if py_files.len() == 1 {
+        return Ok(Some(py_files[0].clone()));
+    }
+
+    // Otherwise look for a single main.py or __main__.py anywhere in the project,
+    // e.g. src/app/main.py, and return its path relative to the project home
+    let mut candidates = Vec::new();
+    find_main_py_files(project_home, Path::new(""), &mut candidates)?;
+    if candidates.len() == 1 {
+        Ok(Some(candidates.remove(0)))
+    } else {
+        Ok(None)
     }
+}
 
-    for entry in fs::read_dir(src)? {
+/// Collect the project-relative paths of all main.py and __main__.py files below `dir`
+fn find_main_py_files(dir: &Path, relative: &Path, found: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
         let file_name = entry.file_name();
 
-        // Skip .venv directories (Python) and node_modules (Node.js)
-        if file_name == ".venv" || file_name == "node_modules" {
-            continue;
-        }
-
-        let dest_path = dst.join(&file_name);
-
-        if path.is_dir() {
-            copy_dir_recursive(&path, &dest_path)?;
-        } else {
-            fs::copy(&path, &dest_path)?;
+        // Symlinked directories are not searched, they may loop back to a parent
+        if entry.file_type()?.is_dir() {
+            if !SKIPPED_DIRS.iter().any(|skipped| file_name == *skipped) {
+                find_main_py_files(&path, &relative.join(&file_name), found)?;
+            }
+        } else if file_name == "main.py" || file_name == "__main__.py" {
+            let relative_path = relative.join(&file_name);
+            found.push(relative_path.to_string_lossy().into_owned());
         }
     }
-
     Ok(())
 }
 
-fn find_single_py_file(project_home: &Path) -> Result<Option<String>, Box<dyn std::error::Error>> {
-    let mut py_files = Vec::new();
+/// List the names of the files with one of the given extensions at the root of the project, sorted
+fn list_root_files(project_home: &Path, extensions: &[&str]) -> io::Result<Vec<String>> {
+    let mut files = Vec::new();
 
     for entry in fs::read_dir(project_home)? {
         let entry = entry?;
@@ -757,26 +6021,25 @@ fn find_single_py_file(project_home: &Path) -> Result<Option<String>, Box<dyn st
 
         // Only check files (not directories) and only at the root level
         if path.is_file()
-            && let Some(extension) = path.extension()
-            && extension == "py"
+            && let Some(extension) = path.extension().and_then(|e| e.to_str())
+            && extensions.contains(&extension)
             && let Some(file_name) = path.file_name()
             && let Some(name_str) = file_name.to_str()
         {
-            py_files.push(name_str.to_string());
+            files.push(name_str.to_string());
         }
     }
 
-    // Return the filename if exactly one .py file is found
-    if py_files.len() == 1 {
-        Ok(Some(py_files[0].clone()))
-    } else {
-        Ok(None)
-    }
+    files.sort();
+    Ok(files)
 }
 
 fn read_name_from_pyproject(project_home: &Path) -> Result<String, Box<dyn std::error::Error>> {
     let pyproject_path = project_home.join("pyproject.toml");
 
+    if !pyproject_path.exists() && has_setuptools_manifest(project_home) {
+        return Ok(read_service_info_from_setup_cfg(project_home).name);
+    }
     if !pyproject_path.exists() {
         return Err(format!("pyproject.toml not found in: {}", project_home.display()).into());
     }
@@ -785,21 +6048,108 @@ fn read_name_from_pyproject(project_home: &Path) -> Result<String, Box<dyn std::
     let value: Value = toml::from_str(&content)?;
 
     // Extract project name
-    let name = value
-        .get("project")
-        .and_then(|p| p.get("name"))
+    let (project, section) = pyproject_metadata_table(&value)?;
+    let name = project
+        .get("name")
         .and_then(|n| n.as_str())
-        .ok_or("Missing 'project.name' in pyproject.toml")?
+        .ok_or_else(|| format!("Missing '{}.name' in pyproject.toml", section))?
         .to_string();
 
     Ok(name)
 }
 
+/// Whether the project has a setup.py or setup.cfg (an older setuptools project)
+fn has_setuptools_manifest(project_home: &Path) -> bool {
+    project_home.join("setup.py").exists() || project_home.join("setup.cfg").exists()
+}
+
+/// Read the metadata of a setuptools project from the `[metadata]` section of setup.cfg.
+/// setup.py cannot be read without running it, so the name falls back to the directory
+/// name and the version to 0.1.0.
+fn read_service_info_from_setup_cfg(project_home: &Path) -> ProjectMetadata {
+    let mut metadata: BTreeMap<String, String> = BTreeMap::new();
+    if let Ok(content) = fs::read_to_string(project_home.join("setup.cfg")) {
+        let mut in_metadata = false;
+        for line in content.lines() {
+            // Indented lines continue a multi-line value, which is not needed here
+            if line.starts_with(char::is_whitespace) {
+                continue;
+            }
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') {
+                in_metadata = line == "[metadata]";
+            } else if in_metadata && let Some(index) = line.find(['=', ':']) {
+                let value = line[index + 1..].trim();
+                // Directives such as "attr: package.__version__" need the code
+                if !value.is_empty() && !value.starts_with("attr:") && !value.starts_with("file:") {
+                    metadata.insert(line[..index].trim().replace('-', "_"), value.to_string());
+                }
+            }
+        }
+    }
+
+    let name = metadata.remove("name").unwrap_or_else(|| {
+        project_home
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let version = metadata
+        .remove("version")
+        .unwrap_or_else(|| DEFAULT_SETUPTOOLS_VERSION.to_string());
+    let maintainers = chart_maintainer(
+        metadata.get("author").map(String::as_str),
+        metadata.get("author_email").map(String::as_str),
+    )
+    .into_iter()
+    .collect();
+
+    ProjectMetadata {
+        name,
+        version,
+        description: metadata.remove("description"),
+        maintainers,
+        license: metadata.remove("license"),
+        homepage: metadata.remove("url"),
+    }
+}
+
+/// The table of pyproject.toml with the project metadata and its name: `[project]`
+/// (PEP 621), else `[tool.poetry]` of a Poetry project
+fn pyproject_metadata_table(value: &Value) -> Result<(&Value, &'static str), String> {
+    if let Some(project) = value.get("project") {
+        return Ok((project, "project"));
+    }
+    value
+        .get("tool")
+        .and_then(|tool| tool.get("poetry"))
+        .map(|poetry| (poetry, "tool.poetry"))
+        .ok_or_else(|| "Missing [project] or [tool.poetry] table in pyproject.toml".to_string())
+}
+
+/// Metadata of a project from its manifest (pyproject.toml, package.json or Cargo.toml)
+struct ProjectMetadata {
+    name: String,
+    version: String,
+    description: Option<String>,
+    /// Chart maintainers as (name, e-mail address)
+    maintainers: Vec<(String, Option<String>)>,
+    /// SPDX license expression
+    license: Option<String>,
+    homepage: Option<String>,
+}
+
 fn read_service_info_from_pyproject(
     project_home: &Path,
-) -> Result<(String, String), Box<dyn std::error::Error>> {
+) -> Result<ProjectMetadata, Box<dyn std::error::Error>> {
     let pyproject_path = project_home.join("pyproject.toml");
 
+    if !pyproject_path.exists() && has_setuptools_manifest(project_home) {
+        return Ok(read_service_info_from_setup_cfg(project_home));
+    }
     if !pyproject_path.exists() {
         return Err(format!("pyproject.toml not found in: {}", project_home.display()).into());
     }
@@ -807,28 +6157,82 @@ fn read_service_info_from_pyproject(
     let content = fs::read_to_string(&pyproject_path)?;
     let value: Value = toml::from_str(&content)?;
 
+    let (project, section) = pyproject_metadata_table(&value)?;
+
     // Extract project name
-    let name = value
-        .get("project")
-        .and_then(|p| p.get("name"))
+    let name = project
+        .get("name")
         .and_then(|n| n.as_str())
-        .ok_or("Missing 'project.name' in pyproject.toml")?
+        .ok_or_else(|| format!("Missing '{}.name' in pyproject.toml", section))?
         .to_string();
 
     // Extract version
-    let version = value
-        .get("project")
-        .and_then(|p| p.get("version"))
+    let version = project
+        .get("version")
         .and_then(|v| v.as_str())
-        .ok_or("Missing 'project.version' in pyproject.toml")?
+        .ok_or_else(|| format!("Missing '{}.version' in pyproject.toml", section))?
         .to_string();
 
-    Ok((name, version))
+    let description = project
+        .get("description")
+        .and_then(|d| d.as_str())
+        .map(|d| d.to_string());
+
+    // Authors are tables with a name and/or an email, or "Name <email>" strings for Poetry
+    let maintainers = project
+        .get("authors")
+        .and_then(|a| a.as_array())
+        .map(|authors| {
+            authors
+                .iter()
+                .filter_map(|author| match author.as_str() {
+                    Some(author) => parse_person(author),
+                    None => chart_maintainer(
+                        author.get("name").and_then(|n| n.as_str()),
+                        author.get("email").and_then(|e| e.as_str()),
+                    ),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // The license is an SPDX expression or (before PEP 639) a table with its text
+    let license = project
+        .get("license")
+        .and_then(|l| {
+            l.as_str()
+                .or_else(|| l.get("text").and_then(|t| t.as_str()))
+        })
+        .map(|l| l.to_string());
+
+    // Poetry has a homepage field; project URLs are free-form, the homepage is
+    // conventionally "Homepage"
+    let homepage = project
+        .get("homepage")
+        .and_then(|h| h.as_str())
+        .or_else(|| {
+            project
+                .get("urls")
+                .and_then(|u| u.as_table())?
+                .iter()
+                .find(|(label, _)| label.eq_ignore_ascii_case("homepage"))
+                .and_then(|(_, url)| url.as_str())
+        })
+        .map(|h| h.to_string());
+
+    Ok(ProjectMetadata {
+        name,
+        version,
+        description,
+        maintainers,
+        license,
+        homepage,
+    })
 }
 
 fn read_service_info_from_package_json(
     project_home: &Path,
-) -> Result<(String, String), Box<dyn std::error::Error>> {
+) -> Result<ProjectMetadata, Box<dyn std::error::Error>> {
     let package_json_path = project_home.join("package.json");
 
     if !package_json_path.exists() {
@@ -852,7 +6256,485 @@ fn read_service_info_from_package_json(
         .unwrap_or("1.0.0")
         .to_string();
 
-    Ok((name, version))
+    let description = value
+        .get("description")
+        .and_then(|d| d.as_str())
+        .map(|d| d.to_string());
+
+    // The author is either "Name <email> (url)" or an object with name and email
+    let maintainers = match value.get("author") {
+        Some(serde_json::Value::String(author)) => parse_person(author).into_iter().collect(),
+        Some(author) => chart_maintainer(
+            author.get("name").and_then(|n| n.as_str()),
+            author.get("email").and_then(|e| e.as_str()),
+        )
+        .into_iter()
+        .collect(),
+        None => Vec::new(),
+    };
+
+    let license = value
+        .get("license")
+        .and_then(|l| l.as_str())
+        .map(|l| l.to_string());
+    let homepage = value
+        .get("homepage")
+        .and_then(|h| h.as_str())
+        .map(|h| h.to_string());
+
+    Ok(ProjectMetadata {
+        name,
+        version,
+        description,
+        maintainers,
+        license,
+        homepage,
+    })
+}
+
+/// Read the package metadata from Cargo.toml
+fn read_service_info_from_cargo_toml(
+    project_home: &Path,
+) -> Result<ProjectMetadata, Box<dyn std::error::Error>> {
+    let cargo_toml_path = project_home.join("Cargo.toml");
+
+    if !cargo_toml_path.exists() {
+        return Err(format!("Cargo.toml not found in: {}", project_home.display()).into());
+    }
+
+    let content = fs::read_to_string(&cargo_toml_path)?;
+    let value: Value = toml::from_str(&content)?;
+
+    // Extract package name
+    let name = value
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .ok_or("Missing 'package.name' in Cargo.toml")?
+        .to_string();
+
+    // Extract version (default to "1.0.0" if not present or inherited from a workspace)
+    let version = value
+        .get("package")
+        .and_then(|p| p.get("version"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("1.0.0")
+        .to_string();
+
+    let description = value
+        .get("package")
+        .and_then(|p| p.get("description"))
+        .and_then(|d| d.as_str())
+        .map(|d| d.to_string());
+
+    // Authors are "Name <email>" strings
+    let maintainers = value
+        .get("package")
+        .and_then(|p| p.get("authors"))
+        .and_then(|a| a.as_array())
+        .map(|authors| {
+            authors
+                .iter()
+                .filter_map(|author| author.as_str().and_then(parse_person))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let license = value
+        .get("package")
+        .and_then(|p| p.get("license"))
+        .and_then(|l| l.as_str())
+        .map(|l| l.to_string());
+    let homepage = value
+        .get("package")
+        .and_then(|p| p.get("homepage"))
+        .and_then(|h| h.as_str())
+        .map(|h| h.to_string());
+
+    Ok(ProjectMetadata {
+        name,
+        version,
+        description,
+        maintainers,
+        license,
+        homepage,
+    })
+}
+
+/// Parse a person such as `Jane Doe <jane@example.com> (https://example.com)` into a chart
+/// maintainer
+fn parse_person(person: &str) -> Option<(String, Option<String>)> {
+    let (name, email) = match person.split_once('<') {
+        Some((name, rest)) => (name, rest.split_once('>').map(|(email, _)| email)),
+        None => (person, None),
+    };
+    chart_maintainer(name.split('(').next(), email)
+}
+
+/// A chart maintainer needs a name (else the e-mail address is used), and helm lint
+/// rejects malformed e-mail addresses, so only plausible ones are kept
+fn chart_maintainer(name: Option<&str>, email: Option<&str>) -> Option<(String, Option<String>)> {
+    let email = email.map(str::trim).filter(|email| {
+        email
+            .split_once('@')
+            .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+            && !email.contains(char::is_whitespace)
+            && email.matches('@').count() == 1
+    });
+    let name = name
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .or(email)?;
+    Some((name.to_string(), email.map(|email| email.to_string())))
+}
+
+/// The deno.json or deno.jsonc of a Deno project, if any
+fn deno_config_path(project_home: &Path) -> Option<PathBuf> {
+    ["deno.json", "deno.jsonc"]
+        .iter()
+        .map(|file_name| project_home.join(file_name))
+        .find(|path| path.is_file())
+}
+
+/// Read deno.json or deno.jsonc, which may contain comments and trailing commas
+fn read_deno_config(project_home: &Path) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let path = deno_config_path(project_home)
+        .ok_or_else(|| format!("deno.json not found in: {}", project_home.display()))?;
+    let content = fs::read_to_string(&path)?;
+    serde_json::from_str(&strip_jsonc(&content))
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e).into())
+}
+
+/// Remove the comments and trailing commas of JSONC, leaving plain JSON
+fn strip_jsonc(content: &str) -> String {
+    let mut json = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            json.push(c);
+            match c {
+                '\\' => json.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                json.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|&c| c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                json.push(' ');
+            }
+            _ => json.push(c),
+        }
+    }
+
+    // A comma directly followed by a closing bracket is a trailing comma
+    let mut result = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in json.char_indices() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' && json[index + 1..].trim_start().starts_with(['}', ']']) {
+            continue;
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Read the name and version of a Deno project from deno.json(c). Both are optional there:
+/// the name falls back to the directory name and the version to 1.0.0.
+fn read_service_info_from_deno_json(
+    project_home: &Path,
+) -> Result<ProjectMetadata, Box<dyn std::error::Error>> {
+    let value = read_deno_config(project_home)?;
+
+    let name = value
+        .get("name")
+        .and_then(|n| n.as_str())
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| {
+            project_home
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+    let version = value
+        .get("version")
+        .and_then(|v| v.as_str())
+        .unwrap_or("1.0.0")
+        .to_string();
+
+    Ok(ProjectMetadata {
+        name,
+        version,
+        description: None,
+        maintainers: Vec::new(),
+        license: None,
+        homepage: None,
+    })
+}
+
+/// A bumped project manifest, not yet written
+struct VersionBump {
+    path: PathBuf,
+    file_name: String,
+    old_version: String,
+    new_version: String,
+    content: String,
+}
+
+/// Bump `part` of the version in the manifest of the project: pyproject.toml, package.json,
+/// Cargo.toml or deno.json. Only the version is replaced, the rest of the file is kept as is.
+/// Returns the manifest with the new version without writing it.
+fn bump_project_version(
+    project_home: &Path,
+    project_type: &str,
+    part: &str,
+) -> Result<VersionBump, ServiceMakerError> {
+    let config_error = |message: String| ServiceMakerError::new(ErrorKind::Config, message);
+    let (path, metadata) = match project_type {
+        "python" => (
+            project_home.join("pyproject.toml"),
+            read_service_info_from_pyproject(project_home),
+        ),
+        "nodejs" => (
+            project_home.join("package.json"),
+            read_service_info_from_package_json(project_home),
+        ),
+        "rust" => (
+            project_home.join("Cargo.toml"),
+            read_service_info_from_cargo_toml(project_home),
+        ),
+        "deno" => (
+            deno_config_path(project_home).unwrap_or_else(|| project_home.join("deno.json")),
+            read_service_info_from_deno_json(project_home),
+        ),
+        _ => {
+            return Err(config_error(format!(
+                "--bump is not supported for {} projects",
+                project_type
+            )));
+        }
+    };
+    let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+    if !path.is_file() {
+        return Err(config_error(format!(
+            "--bump needs the version in {}, which does not exist",
+            file_name
+        )));
+    }
+    let old_version = metadata
+        .map_err(|e| config_error(format!("Failed to read {}: {}", file_name, e)))?
+        .version;
+    let new_version = bump_version(&old_version, part)
+        .map_err(|e| config_error(format!("Cannot bump the version in {}: {}", file_name, e)))?;
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| config_error(format!("Failed to read {}: {}", file_name, e)))?;
+    let content = if file_name.ends_with(".toml") {
+        let table = match project_type {
+            "rust" => "package",
+            _ => toml::from_str::<Value>(&content)
+                .ok()
+                .and_then(|value| pyproject_metadata_table(&value).ok().map(|(_, t)| t))
+                .unwrap_or("project"),
+        };
+        replace_toml_version(&content, table, &old_version, &new_version)
+    } else {
+        replace_json_version(&content, &old_version, &new_version)
+    }
+    .ok_or_else(|| {
+        config_error(format!(
+            "No static version \"{}\" found in {} to bump",
+            old_version, file_name
+        ))
+    })?;
+
+    Ok(VersionBump {
+        path,
+        file_name,
+        old_version,
+        new_version,
+        content,
+    })
+}
+
+/// Increment the major, minor or patch number of a MAJOR.MINOR.PATCH version, resetting the
+/// lower ones. Pre-release and build suffixes are refused, as are other version schemes.
+fn bump_version(version: &str, part: &str) -> Result<String, String> {
+    let numbers = version
+        .split('.')
+        .map(|n| {
+            (!n.is_empty()
+                && n.chars().all(|c| c.is_ascii_digit())
+                && (n == "0" || !n.starts_with('0')))
+            .then(|| n.parse::<u64>().ok())
+            .flatten()
+        })
+        .collect::<Option<Vec<_>>>();
+    let Some([major, minor, patch]) = numbers.as_deref() else {
+        return Err(format!(
+            "'{}' is not a plain SemVer MAJOR.MINOR.PATCH version",
+            version
+        ));
+    };
+    Ok(match part {
+        "major" => format!("{}.0.0", major + 1),
+        "minor" => format!("{}.{}.0", major, minor + 1),
+        _ => format!("{}.{}.{}", major, minor, patch + 1),
+    })
+}
+
+/// Replace `version = "old"` in the `[table]` of a TOML file
+fn replace_toml_version(content: &str, table: &str, old: &str, new: &str) -> Option<String> {
+    let mut current_table = String::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && !trimmed.starts_with("[[") {
+            current_table = trimmed
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default()
+                .replace(' ', "");
+        } else if current_table == table
+            && let Some(value) = trimmed.strip_prefix("version")
+            && let Some(value) = value.trim_start().strip_prefix('=')
+        {
+            let value = value.trim_start();
+            for quote in ['"', '\''] {
+                let quoted = format!("{}{}{}", quote, old, quote);
+                if value.starts_with(&quoted) {
+                    let start = offset + line.find(&quoted)?;
+                    return Some(format!(
+                        "{}{}{}{}{}",
+                        &content[..start],
+                        quote,
+                        new,
+                        quote,
+                        &content[start + quoted.len()..]
+                    ));
+                }
+            }
+            return None;
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Replace the top-level `"version": "old"` of a JSON (or JSONC) file
+fn replace_json_version(content: &str, old: &str, new: &str) -> Option<String> {
+    let bytes = content.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            b'"' => {
+                // Find the closing quote, skipping escaped characters
+                let mut end = i + 1;
+                while end < bytes.len() && bytes[end] != b'"' {
+                    end += if bytes[end] == b'\\' { 2 } else { 1 };
+                }
+                if depth == 1
+                    && content.get(i + 1..end) == Some("version")
+                    && let Some(value) = content[end + 1..].trim_start().strip_prefix(':')
+                {
+                    let value = value.trim_start();
+                    let quoted = format!("\"{}\"", old);
+                    if value.starts_with(&quoted) {
+                        let start = content.len() - value.len();
+                        return Some(format!(
+                            "{}\"{}\"{}",
+                            &content[..start],
+                            new,
+                            &content[start + quoted.len()..]
+                        ));
+                    }
+                }
+                i = end;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// How a Deno project is started
+enum DenoStart {
+    /// `deno task start`
+    Task,
+    /// `deno run` of the module
+    Module(String),
+}
+
+/// Detect how to start a Deno project: its `start` task, else its (`.`) export, else
+/// main.ts / main.js in the project home
+fn detect_deno_entrypoint(project_home: &Path) -> Result<DenoStart, Box<dyn std::error::Error>> {
+    let value = read_deno_config(project_home)?;
+
+    if value.get("tasks").and_then(|t| t.get("start")).is_some() {
+        return Ok(DenoStart::Task);
+    }
+
+    let export = match value.get("exports") {
+        Some(serde_json::Value::String(module)) => Some(module.as_str()),
+        Some(exports) => exports.get(".").and_then(|e| e.as_str()),
+        None => None,
+    };
+    if let Some(module) = export {
+        return Ok(DenoStart::Module(
+            module.trim_start_matches("./").to_string(),
+        ));
+    }
+
+    ["main.ts", "main.js"]
+        .iter()
+        .find(|module| project_home.join(module).is_file())
+        .map(|module| DenoStart::Module(module.to_string()))
+        .ok_or_else(|| "No start task or entrypoint module found for the Deno project".into())
+}
+
+/// Detect the binary of a Rust project from Cargo.toml
+/// Uses the first [[bin]] target, otherwise the package name
+fn detect_rust_binary(project_home: &Path) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(project_home.join("Cargo.toml"))?;
+    let value: Value = toml::from_str(&content)?;
+
+    let bin_name = value
+        .get("bin")
+        .and_then(|b| b.as_array())
+        .and_then(|bins| bins.first())
+        .and_then(|bin| bin.get("name"))
+        .and_then(|n| n.as_str());
+    let package_name = value
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str());
+
+    Ok(bin_name.or(package_name).map(|name| name.to_string()))
 }
 
 fn copy_scripts_to_temp(temp_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
@@ -872,38 +6754,165 @@ fn copy_scripts_to_temp(temp_dir: &Path) -> Result<(), Box<dyn std::error::Error
         fs::set_permissions(&dest_path, perms)?;
     }
 
-    println!("Created scripts directory: {}", scripts_dir.display());
+    debug!("Created scripts directory: {}", scripts_dir.display());
     Ok(())
 }
 
+/// Read the chart templates of --chart-dir as (relative path, content), in the
+/// form of the embedded `CHART_FILES`
+fn read_chart_dir(chart_dir: &Path) -> Result<Vec<(String, String)>, ServiceMakerError> {
+    let config_error = |message: String| {
+        ServiceMakerError::new(ErrorKind::Config, message)
+            .with_context(chart_dir.display().to_string())
+    };
+    if !chart_dir.join("Chart.yaml").is_file() {
+        return Err(config_error(
+            "--chart-dir must contain a Chart.yaml".to_string(),
+        ));
+    }
+
+    let mut chart_files = Vec::new();
+    let mut pending = vec![chart_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| config_error(format!("Failed to read {}: {}", dir.display(), e)))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| config_error(format!("Failed to read {}: {}", dir.display(), e)))?
+                .path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let content = fs::read_to_string(&path).map_err(|e| {
+                config_error(format!(
+                    "Failed to read chart file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            let relative_path = path
+                .strip_prefix(chart_dir)
+                .unwrap()
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            chart_files.push((relative_path, content));
+        }
+    }
+
+    chart_files.sort();
+    info!(
+        "Using {} chart file(s) from {}",
+        chart_files.len(),
+        chart_dir.display()
+    );
+    Ok(chart_files)
+}
+
 fn copy_and_replace_charts(
     dst: &Path,
-    service_name: &str,
-    version: &str,
-    port: u16,
-    image_name: &str,
+    params: &ChartParams,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let probes = params.health_path.map(probes_yaml).unwrap_or_default();
+
     if !dst.exists() {
         fs::create_dir_all(dst)?;
     }
 
-    // Process each embedded chart file
-    for chart_file in CHART_FILES {
+    // Process each chart file
+    for (chart_file_path, chart_file_content) in params.chart_files {
         // Create the full destination path
-        let dest_path = dst.join(chart_file.path);
+        let mut dest_path = dst.join(chart_file_path);
 
         // Create parent directories if needed
         if let Some(parent) = dest_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        // Replace placeholders in the embedded content
-        let modified_content = chart_file
-            .content
-            .replace("{SERVICE_NAME}", service_name)
-            .replace("{VERSION}", version)
-            .replace("{PORT}", &port.to_string())
-            .replace("{IMAGE_NAME}", image_name);
+        // Replace placeholders in the template content
+        let modified_content = chart_file_content
+            .replace("{SERVICE_NAME}", params.service_name)
+            .replace("{CHART_VERSION}", params.chart_version)
+            .replace("{CHART_TYPE}", params.chart_type)
+            .replace("{APP_VERSION}", &yaml_quote(&params.metadata.version))
+            .replace(
+                "{DESCRIPTION}",
+                &yaml_quote(
+                    params
+                        .metadata
+                        .description
+                        .as_deref()
+                        .unwrap_or(&format!("A Helm chart for {}", params.service_name)),
+                ),
+            )
+            .replace(
+                "{MAINTAINERS}",
+                &maintainers_yaml(&params.metadata.maintainers),
+            )
+            .replace(
+                "{HOME}",
+                &yaml_quote(params.metadata.homepage.as_deref().unwrap_or("")),
+            )
+            .replace("{PORT}", &params.port.to_string())
+            .replace("{IMAGE_NAME}", params.image_name)
+            .replace("{PROBES}", &probes)
+            .replace("{REPLICAS}", &params.replicas.to_string())
+            .replace("{SERVICE_TYPE}", params.service_type)
+            .replace("{ENV_VARS}", &env_yaml(params.env))
+            .replace("{CONFIG_MAP}", &config_map_yaml(params.config_map))
+            .replace("{PULL_SECRETS}", &pull_secrets_yaml(params.pull_secrets))
+            .replace("{ANNOTATIONS}\n", &annotations_yaml(params.annotations))
+            .replace("{K8S_LABELS}\n", &k8s_labels_yaml(params.k8s_labels))
+            .replace(
+                "{NODE_PORT}",
+                &params
+                    .node_port
+                    .map_or("null".to_string(), |p| p.to_string()),
+            )
+            .replace(
+                "{INGRESS_ENABLED}",
+                &params.ingress_host.is_some().to_string(),
+            )
+            .replace(
+                "{INGRESS_HOST}",
+                &yaml_quote(params.ingress_host.unwrap_or("")),
+            )
+            .replace(
+                "{INGRESS_CLASS}",
+                &yaml_quote(params.ingress_class.unwrap_or("")),
+            )
+            .replace("{RESOURCES}", &resources_yaml(params));
+        let mut modified_content =
+            substitute_template_vars(&modified_content, params.template_vars);
+
+        // Library charts must not render resources themselves, so each manifest
+        // becomes a named template (e.g. "myservice.deployment") in a helper file
+        if params.chart_type == "library"
+            && let Some(file_name) = chart_file_path.strip_prefix("templates/")
+            && !file_name.starts_with('_')
+        {
+            let stem = file_name.trim_end_matches(".yaml");
+            modified_content = format!(
+                "{{{{- define \"{}.{}\" }}}}\n{}{{{{- end }}}}\n",
+                params.service_name, stem, modified_content
+            );
+            dest_path = dst.join(format!("templates/_{}.tpl", stem));
+        }
+
+        // Per-environment overlays start out as copies of the base values
+        if chart_file_path == "values.yaml" {
+            for environment in params.environments {
+                let overlay = format!(
+                    "# Values for the {env} environment, overriding values.yaml\n\
+                    # Use with: helm install -f values-{env}.yaml ...\n{}",
+                    modified_content,
+                    env = environment
+                );
+                fs::write(dst.join(format!("values-{}.yaml", environment)), overlay)?;
+            }
+        }
 
         // Write modified content
         fs::write(&dest_path, modified_content)?;
@@ -911,3 +6920,214 @@ fn copy_and_replace_charts(
 
     Ok(())
 }
+
+/// Readiness and liveness probe stanzas for the service container
+fn probes_yaml(health_path: &str) -> String {
+    let path = yaml_quote(health_path);
+    ["readinessProbe", "livenessProbe"]
+        .iter()
+        .map(|probe| {
+            format!(
+                "          {}:\n            httpGet:\n              path: {}\n              port: server",
+                probe, path
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The `resources` section of values.yaml; omitted entries are left out so
+/// that the deployment template only renders what was actually requested
+fn resources_yaml(params: &ChartParams) -> String {
+    let mut sections = Vec::new();
+    for (section, cpu, memory) in [
+        ("requests", params.cpu_request, params.memory_request),
+        ("limits", params.cpu_limit, params.memory_limit),
+    ] {
+        if cpu.is_none() && memory.is_none() {
+            continue;
+        }
+        let mut lines = vec![format!("  {}:", section)];
+        if let Some(cpu) = cpu {
+            lines.push(format!("    cpu: {}", yaml_quote(cpu)));
+        }
+        if let Some(memory) = memory {
+            lines.push(format!("    memory: {}", yaml_quote(memory)));
+        }
+        sections.push(lines.join("\n"));
+    }
+
+    if sections.is_empty() {
+        "resources: {}".to_string()
+    } else {
+        format!("resources:\n{}", sections.join("\n"))
+    }
+}
+
+/// Build the `env` section of values.yaml from the --env entries
+fn env_yaml(env: &[(String, String)]) -> String {
+    if env.is_empty() {
+        return "env: []".to_string();
+    }
+    let entries: Vec<String> = env
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "  - name: {}\n    value: {}",
+                yaml_quote(key),
+                yaml_quote(value)
+            )
+        })
+        .collect();
+    format!("env:\n{}", entries.join("\n"))
+}
+
+/// Build the `maintainers` list of Chart.yaml from the project authors
+fn maintainers_yaml(maintainers: &[(String, Option<String>)]) -> String {
+    if maintainers.is_empty() {
+        return "maintainers: []".to_string();
+    }
+    let entries: Vec<String> = maintainers
+        .iter()
+        .map(|(name, email)| match email {
+            Some(email) => format!(
+                "  - name: {}\n    email: {}",
+                yaml_quote(name),
+                yaml_quote(email)
+            ),
+            None => format!("  - name: {}", yaml_quote(name)),
+        })
+        .collect();
+    format!("maintainers:\n{}", entries.join("\n"))
+}
+
+/// Build the `imagePullSecrets` list of values.yaml from the --pull-secret names
+fn pull_secrets_yaml(pull_secrets: &[String]) -> String {
+    if pull_secrets.is_empty() {
+        return "imagePullSecrets: []".to_string();
+    }
+    let entries: Vec<String> = pull_secrets
+        .iter()
+        .map(|name| format!("  - name: {}", yaml_quote(name)))
+        .collect();
+    format!("imagePullSecrets:\n{}", entries.join("\n"))
+}
+
+/// Build the `annotations` block of the deployment and service metadata from the
+/// --annotation entries
+fn annotations_yaml(annotations: &[(String, String)]) -> String {
+    if annotations.is_empty() {
+        return "  annotations: {}\n".to_string();
+    }
+    let entries: String = annotations
+        .iter()
+        .map(|(key, value)| format!("    {}: {}\n", key, yaml_quote(value)))
+        .collect();
+    format!("  annotations:\n{}", entries)
+}
+
+/// Build the extra lines of the deployment and service metadata labels from the
+/// --k8s-label entries (nothing without any)
+fn k8s_labels_yaml(k8s_labels: &[(String, String)]) -> String {
+    k8s_labels
+        .iter()
+        .map(|(key, value)| format!("    {}: {}\n", key, yaml_quote(value)))
+        .collect()
+}
+
+/// Build the `configMap` section of values.yaml from the --env-file entries
+fn config_map_yaml(config_map: &[(String, String)]) -> String {
+    if config_map.is_empty() {
+        return "configMap: {}".to_string();
+    }
+    let entries: Vec<String> = config_map
+        .iter()
+        .map(|(key, value)| format!("  {}: {}", yaml_quote(key), yaml_quote(value)))
+        .collect();
+    format!("configMap:\n{}", entries.join("\n"))
+}
+
+/// Check that a string is a Kubernetes resource quantity such as 500m, 0.5, 128Mi or 2G
+fn is_valid_quantity(quantity: &str) -> bool {
+    const SUFFIXES: &[&str] = &[
+        "", "m", "k", "M", "G", "T", "P", "E", "Ki", "Mi", "Gi", "Ti", "Pi", "Ei",
+    ];
+    let number_end = quantity
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(quantity.len());
+    let (number, suffix) = quantity.split_at(number_end);
+    !number.is_empty()
+        && number.matches('.').count() <= 1
+        && number != "."
+        && SUFFIXES.contains(&suffix)
+}
+
+/// Quote a string for use as a YAML scalar (JSON strings are valid YAML)
+fn yaml_quote(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_python_version_from_arangodb_base_image() {
+        assert_eq!(
+            extract_python_version("arangodb/py12base:latest").as_deref(),
+            Some("3.12")
+        );
+        // Only two-digit minor versions are ArangoDB base images
+        assert_eq!(extract_python_version("arangodb/py3base:latest"), None);
+    }
+
+    #[test]
+    fn extract_python_version_from_official_image() {
+        assert_eq!(
+            extract_python_version("python:3.11-slim").as_deref(),
+            Some("3.11")
+        );
+        assert_eq!(
+            extract_python_version("python:3.11.4").as_deref(),
+            Some("3.11")
+        );
+        assert_eq!(
+            extract_python_version(
+                "python:3.12@sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+            )
+            .as_deref(),
+            Some("3.12")
+        );
+    }
+
+    #[test]
+    fn extract_python_version_from_unrelated_image() {
+        assert_eq!(extract_python_version("debian:bookworm-slim"), None);
+    }
+
+    #[test]
+    fn python_version_option_skips_detection() {
+        assert_eq!(
+            resolve_python_version(
+                Some(" 3.10 "),
+                Path::new("/nonexistent"),
+                "arangodb/py12base:latest"
+            ),
+            "3.10"
+        );
+    }
+
+    #[test]
+    fn parse_port_rejects_out_of_range_and_non_numeric() {
+        assert!(parse_port("0").is_err());
+        assert!(parse_port("65536").is_err());
+        assert!(parse_port("abc").is_err());
+    }
+
+    #[test]
+    fn parse_port_accepts_valid_range() {
+        assert_eq!(parse_port("1"), Ok(1));
+        assert_eq!(parse_port("65535"), Ok(65535));
+        assert_eq!(parse_port(" 8080 "), Ok(8080));
+    }
+}